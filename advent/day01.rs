@@ -1,3 +1,6 @@
+use crate::advent::registry::{Day, Input};
+use crate::solver;
+
 pub fn part1(input: &str) -> i32
 {
     fn fuel(mass: i32) -> i32
@@ -60,6 +63,14 @@ pub fn part2(input: &str) -> i32
     result
 }
 
+pub const DAY: Day = Day
+{
+    num: 1,
+    title: "The Tyranny of the Rocket Equation",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
@@ -1,6 +1,9 @@
 use std::vec::Vec;
 
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::intcode::search;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 pub fn part1(input: &str) -> intcode::ValueType
 {
@@ -17,24 +20,21 @@ pub fn part1(input: &str) -> intcode::ValueType
 pub fn part2(input: &str) -> String
 {
     let codes: Vec<intcode::ValueType> = intcode::parse(&input[..]);
+    let mut search = search::Search::new(&codes);
+    search.addMemory(1, 0..100);
+    search.addMemory(2, 0..100);
 
-    for noun in 0..100
+    match search.find(|computer| computer.mem[0] == 19690720)
     {
-        for verb in 0..100
-        {
-            let mut computer = intcode::IntCodeComputer::new();
-            let mut mem = codes.clone();
-            mem[1] = noun;
-            mem[2] = verb;
-            computer.loadCode(&codes);
-            computer.eval(None);
-
-            if computer.mem[0] == 19690720
-            {
-                return (100 * noun + verb).to_string();
-            }
-            computer.reset();
-        }
+        Ok(Some(found)) => (100 * found.values[0] + found.values[1]).to_string(),
+        _ => String::from("No input found."),
     }
-    String::from("No input found.")
 }
+
+pub const DAY: Day = Day
+{
+    num: 2,
+    title: "1202 Program Alarm",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
@@ -1,8 +1,10 @@
-use std::fs::File;
-use std::io::Write;
-
 use std::vec::Vec;
 
+use crate::lib::vis;
+use crate::lib::svg::{palette, Canvas, Point, Style};
+use crate::advent::registry::{Day, Input};
+use crate::solver;
+
 pub type LengthType = i32;
 pub type CoordType = (LengthType, LengthType);
 
@@ -18,70 +20,6 @@ pub fn dist(p1: CoordType, p2: CoordType) -> LengthType
     (p1.0 - p2.0).abs() + (p1.1 - p2.1).abs()
 }
 
-pub struct Boundary
-{
-    pub top: LengthType,
-    pub right: LengthType,
-    pub bottom: LengthType,
-    pub left: LengthType,
-}
-
-impl Boundary
-{
-    pub fn new() -> Boundary
-    {
-        Boundary { top: 0, right: 0, bottom: 0, left: 0 }
-    }
-
-    pub fn union(b1: &Boundary, b2: &Boundary) -> Boundary
-    {
-        Boundary
-        {
-            top: b1.top.max(b2.top),
-            right: b1.right.max(b2.right),
-            bottom: b1.bottom.min(b2.bottom),
-            left: b1.left.min(b2.left),
-        }
-    }
-
-    pub fn unionWith(&mut self, other: &Boundary)
-    {
-        self.top = self.top.max(other.top);
-        self.right = self.right.max(other.right);
-        self.bottom = self.bottom.min(other.bottom);
-        self.left = self.left.min(other.left);
-    }
-
-    pub fn width(&self) -> LengthType
-    {
-        self.right - self.left
-    }
-
-    pub fn height(&self) -> LengthType
-    {
-        self.top - self.bottom
-    }
-
-    pub fn svgOpening(&self, scale: f64) -> String
-    {
-        format!("<svg width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\"
-xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\">",
-                self.width() as f64 * scale, self.height() as f64 * scale,
-                self.left, self.bottom, self.width(), self.height())
-    }
-
-    pub fn expanded(self, by: LengthType) -> Boundary
-    {
-        Boundary
-        {
-            top: self.top + by,
-            right: self.right + by,
-            bottom: self.bottom - by,
-            left: self.left - by,
-        }
-    }
-}
-
 pub struct Segment
 {
     pub begin: CoordType,
@@ -116,15 +54,6 @@ impl Segment
         }
     }
 
-    fn boundary(&self) -> Boundary
-    {
-        Boundary::union(
-            &Boundary { top: self.begin.1, right: self.begin.0,
-                        bottom: self.begin.1, left: self.begin.0 },
-            &Boundary { top: self.end.1, right: self.end.0,
-                        bottom: self.end.1, left: self.end.0 })
-    }
-
     pub fn len(&self) -> LengthType
     {
         match self.direction
@@ -134,15 +63,6 @@ impl Segment
         }
     }
 
-    pub fn svgSnippet(&self) -> String
-    {
-        match self.direction
-        {
-            SegmentDirection::Horizontal => format!("h {}", self.end.0 - self.begin.0),
-            SegmentDirection::Vertical => format!("v {}", self.end.1 - self.begin.1),
-        }
-    }
-
     pub fn intersect(&self, other: &Segment) -> Option<CoordType>
     {
         if self.direction == other.direction
@@ -200,37 +120,36 @@ pub fn parseWire(s: &str) -> Vec<Segment>
     segments
 }
 
-pub fn wire2SVGPath(wire: &Vec<Segment>, color: &str, width: f64) -> String
+// The corners of a wire, starting from the origin.
+pub fn wirePoints(wire: &Vec<Segment>) -> Vec<Point>
 {
-    let attr: String = wire.iter().map(|seg| { seg.svgSnippet() })
-        .collect::<Vec<String>>().join(" ");
-    format!("<path fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" d=\"M 0 0 {}\" />",
-            color, width, attr)
+    let mut points = vec![(0.0, 0.0)];
+    points.extend(wire.iter().map(|seg| (seg.end.0 as f64, seg.end.1 as f64)));
+    points
 }
 
-pub fn wireBoundary(wire: &Vec<Segment>) -> Boundary
+// Draw the wires, their intersections and the origin.
+fn drawWires(wire1: &Vec<Segment>, wire2: &Vec<Segment>, intersections: &Vec<CoordType>) -> String
 {
-    let mut bound = Boundary::new();
-    for seg in wire
+    let mut canvas = Canvas::new();
+    canvas.setPadding(50.0);
+    canvas.setScale(0.05);
+
+    canvas.layer("wires");
+    canvas.polyline(&wirePoints(wire1), &Style::stroke(palette::BLUE, 20.0));
+    canvas.polyline(&wirePoints(wire2), &Style::stroke(palette::RED, 20.0));
+
+    canvas.layer("intersections");
+    for point in intersections
     {
-        bound.unionWith(&seg.boundary());
+        canvas.circle((point.0 as f64, point.1 as f64), 35.0, &Style::fill(palette::DARK));
     }
-    bound
-}
 
-pub fn svgPoint(point: CoordType, size: f64, color: &str) -> String
-{
-    format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" stroke=\"none\" fill=\"{}\" />",
-            point.0, point.1, size, color)
-}
-
-pub fn svgOrigin(size: f64, width: f64, color: &str) -> String
-{
-    format!(r#"
-<line x1="-{size}" y1="{size}" x2="{size}" y2="-{size}" stroke="{color}" stroke-width="{width}" />
-<line x1="-{size}" y1="-{size}" x2="{size}" y2="{size}" stroke="{color}" stroke-width="{width}" />
-"#,
-            size=size, color=color, width=width)
+    canvas.layer("origin");
+    let origin = Style::stroke(palette::DARK, 20.0);
+    canvas.line((-50.0, 50.0), (50.0, -50.0), &origin);
+    canvas.line((-50.0, -50.0), (50.0, 50.0), &origin);
+    canvas.to_string()
 }
 
 pub fn part1(input: &str) -> LengthType
@@ -239,24 +158,9 @@ pub fn part1(input: &str) -> LengthType
     let segments1 = parseWire(lines.next().expect("Failed to read line"));
     let segments2 = parseWire(lines.next().expect("Failed to read line"));
 
-    // Generate SVG
-    let path = "wires.svg";
-    let bound = Boundary::union(&wireBoundary(&segments1),
-                                       &wireBoundary(&segments2));
-
-    let mut file = File::create(path).expect("Failed to write file.");
-
-    file.write_all(format!("{}\n", bound.expanded(50).svgOpening(0.05)).as_bytes())
-        .expect("Failed to write SVG.");
-    file.write_all(format!("{}\n", wire2SVGPath(&segments1, "#3742fa", 20.0))
-                   .as_bytes())
-        .expect("Failed to write SVG.");
-    file.write_all(format!("{}\n", wire2SVGPath(&segments2, "#ff4757", 20.0))
-                   .as_bytes())
-        .expect("Failed to write SVG.");
-
     // Find intersections
     let mut min_dist = LengthType::max_value();
+    let mut intersections: Vec<CoordType> = Vec::new();
     for seg_from_1 in &segments1
     {
         for seg_from_2 in &segments2
@@ -268,15 +172,13 @@ pub fn part1(input: &str) -> LengthType
                 {
                     min_dist = new_dist;
                 }
-                file.write_all(format!("{}\n", svgPoint(point, 35.0, "#2f3542"))
-                               .as_bytes())
-                    .expect("Failed to write SVG.");
+                intersections.push(point);
             }
         }
     }
 
-    file.write_all(svgOrigin(50.0, 20.0, "#2f3542").as_bytes()).expect("Failed to write SVG.");
-    file.write_all(format!("</svg>\n").as_bytes()).expect("Failed to write SVG.");
+    // Generate SVG
+    vis::save("03-wires.svg", || drawWires(&segments1, &segments2, &intersections));
 
     min_dist
 }
@@ -314,6 +216,14 @@ pub fn part2(input: &str) -> LengthType
     min_dist
 }
 
+pub const DAY: Day = Day
+{
+    num: 3,
+    title: "Crossed Wires",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
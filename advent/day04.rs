@@ -1,4 +1,6 @@
 use std::vec::Vec;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 fn parseInput(input: &str) -> (u32, u32)
 {
@@ -119,6 +121,14 @@ pub fn part2(input: &str) -> u32
     count
 }
 
+pub const DAY: Day = Day
+{
+    num: 4,
+    title: "Secure Container",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
@@ -1,4 +1,6 @@
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 pub fn part1(input: &str) -> String
 {
@@ -19,3 +21,11 @@ pub fn part2(input: &str) -> String
     computer.eval(Some(&input));
     format!("{:?}", computer.output)
 }
+
+pub const DAY: Day = Day
+{
+    num: 5,
+    title: "Sunny with a Chance of Asteroids",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 fn parse(input: &str) -> HashMap<String, Vec<String>>
 {
@@ -98,6 +100,14 @@ pub fn part2(input: &str) -> usize
     }
 }
 
+pub const DAY: Day = Day
+{
+    num: 6,
+    title: "Universal Orbit Map",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
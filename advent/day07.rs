@@ -1,6 +1,8 @@
 use std::vec::Vec;
 
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 fn permutations_inner<T, F, R>(xs: &mut Vec<&T>, end: usize, do_what: &mut F)
 where F: FnMut(&Vec<&T>) -> R
@@ -180,6 +182,14 @@ pub fn part2(input: &str) -> intcode::ValueType
     feedback(&mut amps[..], 0)
 }
 
+pub const DAY: Day = Day
+{
+    num: 7,
+    title: "Amplification Circuit",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
@@ -1,4 +1,6 @@
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 pub fn part1(input: &str) -> intcode::ValueType
 {
@@ -17,3 +19,11 @@ pub fn part2(input: &str) -> intcode::ValueType
     assert_eq!(computer.output.len(), 1);
     computer.output[0]
 }
+
+pub const DAY: Day = Day
+{
+    num: 9,
+    title: "Sensor Boost",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
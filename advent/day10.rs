@@ -1,10 +1,11 @@
 use std::vec::Vec;
 use std::collections::HashSet;
 use std::cmp::Ordering;
-use std::fs::File;
-use std::io::Write;
-
 use crate::lib::ratio::Ratio;
+use crate::lib::vis;
+use crate::lib::svg::{palette, Canvas, Style};
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 // enum SpacePoint
 // {
@@ -374,79 +375,63 @@ pub fn part1(input: &str) -> i32
     max_count
 }
 
-#[allow(unused_must_use)]
-pub fn part2(input: &str) -> i32
+// Draw the map with the station, and number the asteroids in the
+// order they are shot.
+fn drawShooting(space: &SpaceInfo, from: &(i32, i32), order: &Vec<(i32, i32)>) -> String
 {
-    let svg_cell_size: i32 = 32;
-    let svg_padding: i32 = 4;
-    let space = parse(input);
-    let (from, _) = findLocation(&space.asteroids);
+    let cell_size = 32.0;
+    let padding = 4.0;
+    let (width, height) = (space.width as f64 * cell_size, space.height as f64 * cell_size);
 
-    let svg_fg = "#ffffff";
-    let svg_bg = "#2f3542";
-
-    let mut svgfile = File::create("10-2.svg").expect("Failed to open SVG file");
-    writeln!(svgfile, r#"<svg viewBox="{} {} {} {}" xmlns="http://www.w3.org/2000/svg">"#,
-             -svg_padding, -svg_padding,
-             space.width * svg_cell_size + 2 * svg_padding,
-             space.height * svg_cell_size + 2 * svg_padding);
-    writeln!(svgfile, r##"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" />"##,
-             -svg_padding, -svg_padding,
-             space.width * svg_cell_size + 2 * svg_padding,
-             space.height * svg_cell_size + 2 * svg_padding,
-             svg_bg);
-    writeln!(svgfile, r##"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="#2ed573" stroke="none" />"##,
-             x=from.0 * svg_cell_size, y=from.1 * svg_cell_size, size=svg_cell_size);
+    let mut canvas = Canvas::new();
+    canvas.setViewBox(-padding, -padding, width + 2.0 * padding, height + 2.0 * padding);
+    canvas.rect(-padding, -padding, width + 2.0 * padding, height + 2.0 * padding,
+                &Style::fill(palette::DARK));
 
+    canvas.layer("asteroids");
+    canvas.cell(from.0 as i64, from.1 as i64, cell_size, &Style::fill(palette::GREEN));
     for asteroid in &space.asteroids
     {
-        if asteroid != &from
+        if asteroid != from
         {
-            writeln!(svgfile, r##"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="#ff4757" stroke="none" />"##,
-                     x=asteroid.0 * svg_cell_size, y=asteroid.1 * svg_cell_size,
-                     size=svg_cell_size);
+            canvas.cell(asteroid.0 as i64, asteroid.1 as i64, cell_size, &Style::fill(palette::RED));
         }
     }
 
-    let mut the200: (i32, i32) = (0, 0);
-    let mut count = 0;
-    shoot(&from, &space.asteroids,
-          |target|
-          {
-              count += 1;
-              if count == 200
-              {
-                  the200 = target.clone();
-              }
-
-              writeln!(svgfile, r#"<text x="{x}" y="{y}" text-anchor="middle" dominant-baseline="middle" fill="{color}" font-family="IBM Plex Mono" font-weight="bold" font-size="13">{text}</text>"#,
-                       x=target.0 * svg_cell_size + svg_cell_size / 2,
-                       y=target.1 * svg_cell_size + svg_cell_size / 2,
-                       color=svg_fg,
-                       text=count);
-
-          });
-
-    writeln!(svgfile, r#"<rect x="0" y="0" width="{}" height="{}" stroke="{}" fill="none" stroke-width="2" />"#,
-             space.width * svg_cell_size, space.height * svg_cell_size, svg_fg);
-
-    for row in 1..space.height
+    canvas.layer("order");
+    for (i, target) in order.iter().enumerate()
     {
-        writeln!(svgfile, r#"<line x1="0" x2="{x}" y1="{y}" y2="{y}"
-stroke="{color}" stroke-width="2"/>"#,
-                 x=space.width * svg_cell_size, y=row * svg_cell_size, color=svg_fg);
+        canvas.text(((target.0 as f64 + 0.5) * cell_size, (target.1 as f64 + 0.5) * cell_size),
+                    &(i + 1).to_string(), 13.0, &Style::fill(palette::WHITE));
     }
 
-    for col in 1..space.width
-    {
-        writeln!(svgfile, r#"<line x1="{x}" x2="{x}" y1="0" y2="{y}" stroke="{color}" stroke-width="2"/>"#,
-                 x=col * svg_cell_size, y=space.height * svg_cell_size, color=svg_fg);
-    }
+    canvas.layer("grid");
+    canvas.grid((0.0, 0.0), space.width as usize, space.height as usize, cell_size,
+                &Style::stroke(palette::WHITE, 2.0));
+    canvas.to_string()
+}
 
-    writeln!(svgfile, "</svg>");
+pub fn part2(input: &str) -> i32
+{
+    let space = parse(input);
+    let (from, _) = findLocation(&space.asteroids);
+
+    let mut order: Vec<(i32, i32)> = Vec::new();
+    shoot(&from, &space.asteroids, |target| order.push(target.clone()));
+    vis::save("10-2.svg", || drawShooting(&space, &from, &order));
+
+    let the200 = order.get(199).cloned().unwrap_or((0, 0));
     the200.0 * 100 + the200.1
 }
 
+pub const DAY: Day = Day
+{
+    num: 10,
+    title: "Monitoring Station",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
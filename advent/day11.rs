@@ -1,9 +1,12 @@
 use std::vec::Vec;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::Write;
-
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::lib::{term, vis};
+use crate::lib::grid::{Bounds, Coord};
+use crate::lib::svg::{palette, Canvas, Style};
+use crate::lib::term::{Cell, Screen};
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 #[derive(Copy, Clone)]
 enum PaintColor
@@ -158,37 +161,125 @@ impl PaintBot
     pub fn run<F, R>(&mut self, mut do_what: F) -> Result<(), String>
         where F: FnMut(&(usize, usize), &PaintColor) -> R
     {
+        let mut live = term::Live::new();
         while !self.core.halted()
         {
             self.step(&mut do_what)?;
+            live.frame(|| drawScreen(self));
         }
+        live.finish();
         Ok(())
     }
 }
 
-#[allow(unused_must_use)]
-fn drawSVG(filename: &str, canvas: &Vec<PaintColor>)
+// How each color looks, in the pictures and in the terminal.
+fn paintColor(color: PaintColor) -> &'static str
 {
-    let svg_cell_size = 4;
+    match color
+    {
+        PaintColor::Black => palette::BLACK,
+        PaintColor::White => palette::WHITE,
+    }
+}
 
-    let mut svgfile = File::create(filename).expect("Failed to open SVG file");
-    writeln!(svgfile, r#"<svg viewBox="{} {} {} {}" xmlns="http://www.w3.org/2000/svg">"#,
-             0, 0, PaintBot::WIDTH * svg_cell_size,
-             PaintBot::HEIGHT * svg_cell_size);
+fn drawSVG(canvas: &Vec<PaintColor>) -> String
+{
+    let svg_cell_size = 4.0;
 
+    let mut svg = Canvas::new();
+    svg.setViewBox(0.0, 0.0, PaintBot::WIDTH as f64 * svg_cell_size,
+                   PaintBot::HEIGHT as f64 * svg_cell_size);
     for i in 0..canvas.len()
     {
         let coord = PaintBot::index2Coord(i);
-        writeln!(svgfile, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="none" />"#,
-                 coord.0 * svg_cell_size, coord.1 * svg_cell_size,
-                 svg_cell_size, svg_cell_size,
-                 match canvas[i]
-                 {
-                     PaintColor::Black => "black",
-                     PaintColor::White => "white",
-                 });
+        svg.cell(coord.0 as i64, coord.1 as i64, svg_cell_size, &Style::fill(paintColor(canvas[i])));
+    }
+    svg.to_string()
+}
+
+// The painted part of the hull, and the bot on it.
+fn drawScreen(bot: &PaintBot) -> Screen
+{
+    let toCoord = |coord: &(usize, usize)| -> Coord { (coord.0 as i64, coord.1 as i64) };
+    let coords: Vec<Coord> = bot.painted.iter().chain(&[bot.location]).map(toCoord).collect();
+    // A cell around it, as far as the hull goes.
+    let around = Bounds::of(&coords).expanded(1);
+    let bounds = Bounds::new((around.x.0.max(0), around.x.1.min(PaintBot::WIDTH as i64)),
+                             (around.y.0.max(0), around.y.1.min(PaintBot::HEIGHT as i64)));
+    let mut screen = Screen::new(bounds);
+    for coord in bounds.coords()
+    {
+        let color = paintColor(bot.canvas[PaintBot::coord2Index(&(coord.0 as usize, coord.1 as usize))]);
+        screen.set(&coord, Cell::block(color));
     }
-    writeln!(svgfile, "</svg>");
+    let arrow = match bot.direction
+    {
+        Direction::Up => '▲',
+        Direction::Right => '▶',
+        Direction::Down => '▼',
+        Direction::Left => '◀',
+    };
+    screen.set(&toCoord(&bot.location), Cell::glyph(arrow, palette::RED).on(palette::DARK));
+    screen.setStatus(&format!("Painted: {}", bot.painted.len()));
+    screen
+}
+
+// The letters that come out of the puzzles, 4 cells wide and 6 tall,
+// with a column of space between them.
+const FONT: &[(char, [&str; 6])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+// Read the white cells on `canvas' as text. Letters that are not in
+// the font come out as `?'.
+fn readLetters(canvas: &[PaintColor]) -> String
+{
+    let white: Vec<(usize, usize)> = (0..canvas.len())
+        .filter(|i| matches!(canvas[*i], PaintColor::White))
+        .map(PaintBot::index2Coord).collect();
+    let left = match white.iter().map(|c| c.0).min()
+    {
+        Some(left) => left,
+        None => { return String::new(); },
+    };
+    let right = white.iter().map(|c| c.0).max().unwrap();
+    let top = white.iter().map(|c| c.1).min().unwrap();
+
+    let cell = |x: usize, y: usize| -> char
+    {
+        if x < PaintBot::WIDTH && y < PaintBot::HEIGHT
+            && matches!(canvas[PaintBot::coord2Index(&(x, y))], PaintColor::White)
+        {
+            '#'
+        }
+        else
+        {
+            '.'
+        }
+    };
+    (left..=right).step_by(5).map(
+        |x|
+        {
+            let glyph: Vec<String> = (top..top + 6)
+                .map(|y| (x..x + 4).map(|x| cell(x, y)).collect()).collect();
+            FONT.iter().find(|(_, rows)| rows.iter().zip(&glyph).all(|(a, b)| a == b))
+                .map_or('?', |(letter, _)| *letter)
+        }).collect()
 }
 
 pub fn part1(input: &str) -> usize
@@ -196,16 +287,56 @@ pub fn part1(input: &str) -> usize
     let code = intcode::parse(input);
     let mut bot = PaintBot::withCode(&code);
     bot.run(|_, _| {}).expect("Failed");
-    drawSVG("11-1.svg", &bot.canvas);
+    vis::save("11-1.svg", || drawSVG(&bot.canvas));
     bot.painted.len()
 }
 
-pub fn part2(input: &str) -> i32
+pub fn part2(input: &str) -> String
 {
     let code = intcode::parse(input);
     let mut bot = PaintBot::withCode(&code);
     bot.canvas[PaintBot::coord2Index(&bot.location)] = PaintColor::White;
     bot.run(|_, _| {}).expect("Failed");
-    drawSVG("11-2.svg", &bot.canvas);
-    0
+    vis::save("11-2.svg", || drawSVG(&bot.canvas));
+    readLetters(&bot.canvas)
+}
+
+pub const DAY: Day = Day
+{
+    num: 11,
+    title: "Space Police",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
+#[test]
+fn testReadLetters()
+{
+    let mut canvas = vec![PaintColor::Black; PaintBot::WIDTH * PaintBot::HEIGHT];
+    let rows = ["#..#.####.#...",
+                "#..#.#....#...",
+                "####.###..#...",
+                "#..#.#....#...",
+                "#..#.#....#...",
+                "#..#.####.####"];
+    for (y, row) in rows.iter().enumerate()
+    {
+        for (x, c) in row.chars().enumerate()
+        {
+            if c == '#'
+            {
+                canvas[PaintBot::coord2Index(&(x + 3, y + 7))] = PaintColor::White;
+            }
+        }
+    }
+    assert_eq!(readLetters(&canvas), "HEL");
+    canvas[PaintBot::coord2Index(&(4, 7))] = PaintColor::White;
+    assert_eq!(readLetters(&canvas), "?EL");
+}
+
+#[test]
+fn testReadLettersInput()
+{
+    // The identifier painted by the real program.
+    assert_eq!(part2(include_str!("../inputs/11.txt").trim()), "PGUEHCJH");
 }
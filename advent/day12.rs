@@ -5,6 +5,8 @@ use regex::Regex;
 
 use crate::lib::vector::Vec3;
 use crate::lib::ratio;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 #[derive(Copy, Clone, fmt::Debug)]
 struct Moon
@@ -210,13 +212,13 @@ fn findPeriodDirection(initial: &Universe, dir_idx: usize) -> u64
 
     loop
     {
-        io::stdout().flush().unwrap();
+        io::stderr().flush().unwrap();
 
         universe.stepDirection(dir_idx);
         steps += 1;
         if universe.eqDirection(initial, dir_idx)
         {
-            println!("Did {} steps.", steps);
+            eprintln!("Did {} steps.", steps);
             return steps;
         }
     }
@@ -239,9 +241,9 @@ fn findPeriodBrutal(initial: &Universe) -> u64
     {
         if steps % 10000000 == 0
         {
-            print!(".");
+            eprint!(".");
         }
-        io::stdout().flush().unwrap();
+        io::stderr().flush().unwrap();
 
         universe.step();
         steps += 1;
@@ -258,6 +260,14 @@ pub fn part2(input: &str) -> u64
     findPeriod(&universe)
 }
 
+pub const DAY: Day = Day
+{
+    num: 12,
+    title: "The N-Body Problem",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
+
 #[test]
 fn testPart1()
 {
@@ -1,14 +1,27 @@
 use std::stringify;
 use std::string::String;
 use std::fmt;
-use std::fmt::Write as FmtWrite;
 use std::cmp::PartialEq;
 use std::collections::HashMap;
+#[cfg(unix)]
 use std::fs::File;
-use std::io::Write;
+#[cfg(unix)]
+use std::io::Read;
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+#[cfg(unix)]
+use std::sync::mpsc;
+#[cfg(unix)]
+use std::thread;
 
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::lib::{term, vis};
+use crate::lib::grid::Bounds;
+use crate::lib::svg::{palette, Canvas, Style};
+use crate::lib::term::{Cell, Screen};
 use crate::makeIntEnum;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 makeIntEnum!
 {
@@ -23,53 +36,74 @@ makeIntEnum!
     derive(Copy, Clone, PartialEq)
 }
 
-fn drawField(field: &HashMap<(intcode::ValueType, intcode::ValueType), TileType>) -> String
+type FieldType = HashMap<(intcode::ValueType, intcode::ValueType), TileType>;
+
+// How each tile looks, in the pictures and in the terminal.
+fn tileColor(tile: TileType) -> Option<&'static str>
 {
-    let mut svg = String::new();
-    let tile_size = 16;
-    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg">"#).expect("Failed to write SVG.");
+    match tile
+    {
+        TileType::Wall => Some(palette::GREY),
+        TileType::Block => Some(palette::BLUE),
+        TileType::Ball => Some(palette::RED),
+        TileType::Paddle => Some(palette::BLACK),
+        TileType::Empty => None,
+    }
+}
+
+fn drawField(field: &FieldType) -> Canvas
+{
+    let mut canvas = Canvas::new();
+    let tile_size = 16.0;
     for (loc, tile) in field
     {
-        match tile
+        let color = match tileColor(*tile)
         {
-            TileType::Wall =>
-            {
-                writeln!(svg, r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{color}" stroke="none" />"#,
-                         x=loc.0 * tile_size, y=loc.1 * tile_size, size=tile_size,
-                         color="grey").expect("Failed to write SVG.");
-            },
-
-            TileType::Block =>
-            {
-                writeln!(svg, r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{color}" stroke="none" />"#,
-                         x=loc.0 * tile_size, y=loc.1 * tile_size,
-                         size=tile_size, color="blue").expect("Failed to write SVG.");
-            },
-
-            TileType::Ball =>
-            {
-                writeln!(svg, r#"<circle cx="{x}" cy="{y}" r="{size}" fill="{color}" stroke="none" />"#,
-                         x=loc.0 * tile_size + tile_size / 2,
-                         y=loc.1 * tile_size + tile_size / 2,
-                         size=tile_size / 2, color="red").expect("Failed to write SVG.");
-            },
-
-            TileType::Paddle =>
-            {
-                writeln!(svg, r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{color}" stroke="none" />"#,
-                         x=loc.0 * tile_size, y=loc.1 * tile_size, size=tile_size, color="black")
-                    .expect("Failed to write SVG.");
-            },
-
-            TileType::Empty => {},
+            Some(color) => color,
+            None => { continue; },
+        };
+        if *tile == TileType::Ball
+        {
+            let center = ((loc.0 as f64 + 0.5) * tile_size, (loc.1 as f64 + 0.5) * tile_size);
+            canvas.circle(center, tile_size / 2.0, &Style::fill(color));
+        }
+        else
+        {
+            canvas.cell(loc.0, loc.1, tile_size, &Style::fill(color));
         }
     }
+    canvas
+}
 
-    writeln!(svg, "</svg>").expect("Failed to write SVG.");
-    svg
+fn drawScreen(game: &Game, help: &str) -> Screen
+{
+    let mut screen = Screen::new(Bounds::of(game.field.keys()));
+    // The terminal is dark, so the paddle is white there.
+    for (loc, tile) in &game.field
+    {
+        match (tile, tileColor(*tile))
+        {
+            (TileType::Ball, Some(color)) => screen.set(loc, Cell::glyph('●', color)),
+            (TileType::Paddle, _) => screen.set(loc, Cell::block(palette::WHITE)),
+            (_, Some(color)) => screen.set(loc, Cell::block(color)),
+            (_, None) => {},
+        }
+    }
+    let mut status = format!("Score: {}  Blocks: {}", game.score, game.count(TileType::Block));
+    if game.over()
+    {
+        status += "  Game over";
+    }
+    if !help.is_empty()
+    {
+        status += "  ";
+        status += help;
+    }
+    screen.setStatus(&status);
+    screen
 }
 
-fn runGame(code: &Vec<intcode::ValueType>) -> HashMap<(intcode::ValueType, intcode::ValueType), TileType>
+fn runGame(code: &Vec<intcode::ValueType>) -> FieldType
 {
     let mut computer = intcode::IntCodeComputer::new();
     let mut field = HashMap::new();
@@ -91,70 +125,299 @@ fn runGame(code: &Vec<intcode::ValueType>) -> HashMap<(intcode::ValueType, intco
     }
 }
 
-fn runGameWithScore(code: &Vec<intcode::ValueType>) -> intcode::ValueType
+// A game of breakout with free play, which pauses every time it reads
+// the joystick.
+struct Game
 {
-    let mut computer = intcode::IntCodeComputer::new();
-    let mut field = HashMap::new();
-    computer.loadCode(code);
-    computer.mem[0] = 2;
-    let mut ball_x: intcode::ValueType = -1;
-    let mut pad_x: intcode::ValueType = -1;
-    let mut score = 0;
-    let mut input: Option<intcode::ValueType> = None;
-    let mut frame = 0;
-    loop
+    computer: intcode::IntCodeComputer,
+    field: FieldType,
+    score: intcode::ValueType,
+}
+
+// Everything about a game at some point, to go back to.
+#[derive(Clone)]
+struct SaveState
+{
+    snapshot: intcode::Snapshot,
+    field: FieldType,
+    score: intcode::ValueType,
+}
+
+impl Game
+{
+    fn new(code: &Vec<intcode::ValueType>) -> Self
+    {
+        let mut computer = intcode::IntCodeComputer::new();
+        computer.loadCode(code);
+        computer.mem[0] = 2;
+        let mut game = Game { computer, field: HashMap::new(), score: 0 };
+        game.advance();
+        game
+    }
+
+    // Run until the game reads the joystick again, or is over, and
+    // take in what it drew.
+    fn advance(&mut self)
     {
-        let x = if let Some(output) = computer.pipe(input)
+        self.computer.run();
+        for output in self.computer.takeOutput().chunks(3)
         {
-            output
+            match output
+            {
+                [-1, 0, score] => { self.score = *score; },
+                [x, y, tile] =>
+                {
+                    let tile = TileType::from(*tile).expect("Unknown tile");
+                    self.field.insert((*x, *y), tile);
+                },
+                _ => panic!("Incomplete output"),
+            }
         }
-        else
+    }
+
+    // Hold the joystick at `position' for the next tick: -1 is left,
+    // 0 is neutral and 1 is right.
+    fn tick(&mut self, position: intcode::ValueType)
+    {
+        if !self.over()
         {
-            return score;
-        };
+            self.computer.pushInput(position);
+            self.advance();
+        }
+    }
 
-        let y = computer.pipe(input).unwrap();
+    fn over(&self) -> bool
+    {
+        self.computer.halted()
+    }
 
-        if x == -1 && y == 0
+    // The x coordinate of a `tile', if there is one.
+    fn find(&self, tile: TileType) -> Option<intcode::ValueType>
+    {
+        self.field.iter().find(|(_, t)| **t == tile).map(|(loc, _)| loc.0)
+    }
+
+    fn count(&self, tile: TileType) -> usize
+    {
+        self.field.values().filter(|t| **t == tile).count()
+    }
+
+    fn save(&self) -> SaveState
+    {
+        SaveState { snapshot: self.computer.snapshot(), field: self.field.clone(), score: self.score }
+    }
+
+    fn load(&mut self, state: &SaveState)
+    {
+        self.computer.restore(&state.snapshot);
+        self.field = state.field.clone();
+        self.score = state.score;
+    }
+}
+
+// What a controller wants done on a tick. Only the keyboard saves and
+// loads.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(not(unix), allow(dead_code))]
+enum Action
+{
+    Joystick(intcode::ValueType),
+    Save,
+    Load,
+    Quit,
+}
+
+// Plays a game. Called every time the game reads the joystick, and
+// again after it is over, until it says to quit.
+trait Controller
+{
+    fn act(&mut self, game: &Game) -> Action;
+
+    // What the keys do, to show under the game.
+    fn help(&self) -> &str { "" }
+}
+
+// Keeps the paddle under the ball, which never misses.
+struct AutoPlayer;
+
+impl Controller for AutoPlayer
+{
+    fn act(&mut self, game: &Game) -> Action
+    {
+        if game.over()
         {
-            score = computer.pipe(input).unwrap();
+            return Action::Quit;
         }
-        else
+        match (game.find(TileType::Paddle), game.find(TileType::Ball))
         {
-            let tile = TileType::from(computer.pipe(input).unwrap())
-                .expect("Unknown tile");
-            field.insert((x, y), tile);
+            (Some(paddle), Some(ball)) => Action::Joystick((ball - paddle).signum()),
+            _ => Action::Joystick(0),
+        }
+    }
+}
 
-            if tile == TileType::Paddle
+// Run stty with `args' on the terminal, and return what it prints.
+#[cfg(unix)]
+fn stty(args: &[&str]) -> Result<String, String>
+{
+    let tty = File::open("/dev/tty").map_err(|e| format!("No terminal: {}", e))?;
+    let output = Command::new("stty").args(args).stdin(tty).stderr(Stdio::inherit()).output()
+        .map_err(|e| format!("Failed to run stty: {}", e))?;
+    if !output.status.success()
+    {
+        return Err(String::from("Failed to set up the terminal."));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// A person at the keyboard. Keys are read as they are pressed, and
+// the joystick is neutral on a tick with no key.
+#[cfg(unix)]
+struct Keyboard
+{
+    keys: mpsc::Receiver<Action>,
+    // Terminal settings to go back to.
+    settings: String,
+}
+
+#[cfg(unix)]
+impl Keyboard
+{
+    fn new() -> Result<Self, String>
+    {
+        let mut tty = File::open("/dev/tty").map_err(|e| format!("No terminal: {}", e))?;
+        let settings = stty(&["-g"])?;
+        // Keys one at a time and without echo. ^C comes in as a key
+        // too, so that it quits and the settings are put back.
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+
+        let (sender, keys) = mpsc::channel();
+        thread::spawn(move ||
+        {
+            let mut escape = 0;
+            let mut byte = [0u8];
+            while tty.read(&mut byte).map_or(false, |n| n == 1)
             {
-                pad_x = x;
+                // Arrow keys are “ESC [ C” and “ESC [ D”.
+                let action = match (escape, byte[0])
+                {
+                    (_, 0x1b) => { escape = 1; continue; },
+                    (1, b'[') => { escape = 2; continue; },
+                    (2, b'D') | (_, b'a') | (_, b'h') => Some(Action::Joystick(-1)),
+                    (2, b'C') | (_, b'd') | (_, b'l') => Some(Action::Joystick(1)),
+                    (_, b' ') => Some(Action::Joystick(0)),
+                    (_, b's') => Some(Action::Save),
+                    (_, b'r') => Some(Action::Load),
+                    (_, b'q') | (_, 0x03) => Some(Action::Quit),
+                    _ => None,
+                };
+                escape = 0;
+                if let Some(action) = action
+                {
+                    if sender.send(action).is_err() || action == Action::Quit
+                    {
+                        return;
+                    }
+                }
             }
-            else if tile == TileType::Ball
+        });
+        Ok(Keyboard { keys, settings })
+    }
+}
+
+#[cfg(unix)]
+impl Controller for Keyboard
+{
+    fn act(&mut self, game: &Game) -> Action
+    {
+        if game.over()
+        {
+            // Wait for a load, or the end.
+            return match self.keys.recv()
+            {
+                Ok(Action::Joystick(_)) | Ok(Action::Save) => Action::Joystick(0),
+                Ok(action) => action,
+                Err(_) => Action::Quit,
+            };
+        }
+
+        // The last key pressed since the tick before decides, but
+        // anything other than moving goes first.
+        let mut result = Action::Joystick(0);
+        while let Ok(action) = self.keys.try_recv()
+        {
+            match action
             {
-                ball_x = x;
-                let mut output = File::create(format!("frames/{:05}.svg", frame))
-                    .unwrap();
-                write!(output, "{}", drawField(&field)).expect("Failed to write SVG.");
-                frame += 1;
+                Action::Joystick(_) => { result = action; },
+                _ => { return action; },
             }
+        }
+        result
+    }
+
+    fn help(&self) -> &str
+    {
+        "←/→: move  s: save  r: restore  q: quit"
+    }
+}
 
-            if pad_x != -1 && ball_x != -1
+#[cfg(unix)]
+impl Drop for Keyboard
+{
+    fn drop(&mut self)
+    {
+        let _ = stty(&[&self.settings]);
+    }
+}
+
+// Play `game' with `controller' until it says to quit, showing it in
+// `live', and return the score.
+fn play(game: &mut Game, controller: &mut dyn Controller, mut live: term::Live)
+        -> intcode::ValueType
+{
+    // The game draws the whole screen before reading the joystick the
+    // first time, so every frame is complete.
+    let mut animation = vis::Animation::new("13-game");
+    let mut saved: Option<SaveState> = None;
+    loop
+    {
+        animation.frame(|| drawField(&game.field));
+        live.frame(|| drawScreen(game, controller.help()));
+        match controller.act(game)
+        {
+            Action::Joystick(position) => { game.tick(position); },
+            Action::Save => { saved = Some(game.save()); },
+            Action::Load =>
             {
-                if pad_x < ball_x
+                if let Some(state) = &saved
                 {
-                    input = Some(1);
+                    game.load(state);
                 }
-                else if pad_x > ball_x
-                {
-                    input = Some(-1);
-                }
-                else
-                {
-                    input = Some(0);
-                }
-            }
+            },
+            Action::Quit => { break; },
         }
     }
+    animation.finish();
+    live.finish();
+    game.score
+}
+
+// Play the game on the keyboard in the terminal, at `fps' ticks per
+// second, and return the score.
+#[cfg(unix)]
+pub fn playInteractive(input: &str, fps: f64) -> Result<intcode::ValueType, String>
+{
+    let code = intcode::parse(input);
+    let mut keyboard = Keyboard::new()?;
+    let live = term::Live::withFps(fps)?;
+    Ok(play(&mut Game::new(&code), &mut keyboard, live))
+}
+
+// Reading keys as they are pressed needs stty.
+#[cfg(not(unix))]
+pub fn playInteractive(_input: &str, _fps: f64) -> Result<intcode::ValueType, String>
+{
+    Err(String::from("Playing day 13 needs a Unix terminal."))
 }
 
 pub fn part1(input: &str) -> usize
@@ -176,5 +439,13 @@ pub fn part1(input: &str) -> usize
 pub fn part2(input: &str) -> intcode::ValueType
 {
     let code = intcode::parse(input);
-    runGameWithScore(&code)
+    play(&mut Game::new(&code), &mut AutoPlayer, term::Live::new())
 }
+
+pub const DAY: Day = Day
+{
+    num: 13,
+    title: "Care Package",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
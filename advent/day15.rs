@@ -1,16 +1,18 @@
 use std::fmt;
 use std::vec::Vec;
 use std::collections::HashSet;
-use std::fs;
-use std::path::Path;
-use std::io::Write;
-use std::fmt::Write as FmtWrite;
 
-use crate::intcode::intcode;
+use crate::intcode;
+use crate::lib::{term, vis};
+use crate::lib::svg::{palette, Canvas, Style};
+use crate::lib::term::{Cell, Screen};
+use crate::lib::grid::{Bounds, Direction, Grid};
 use crate::makeIntEnum;
+use crate::advent::registry::{Day, Input};
+use crate::solver;
 
 type CoordType = (intcode::ValueType, intcode::ValueType);
-const SVG_CELL_SIZE: intcode::ValueType = 4;
+const SVG_CELL_SIZE: f64 = 4.0;
 
 makeIntEnum!
 {
@@ -103,49 +105,55 @@ makeIntEnum!
     derive(Copy, Clone, PartialEq)
 }
 
-#[allow(dead_code)]
-fn drawState(filename: &Path, pos: &CoordType, walls: &HashSet<CoordType>,
-             path: &Vec<CoordType>, target: &Option<CoordType>,
-             probed: &HashSet<CoordType>)
+// Cells of the exploration so far and their colors, in the order they
+// are drawn, for the pictures and the terminal.
+fn stateColors(pos: &CoordType, walls: &HashSet<CoordType>,
+               path: &Vec<CoordType>, target: &Option<CoordType>,
+               probed: &HashSet<CoordType>) -> Vec<(CoordType, &'static str)>
 {
-    let mut svg = fs::File::create(filename).unwrap();
-    let drawBox = |f: &mut fs::File, at: &CoordType, color: &str|
-    {
-        writeln!(f, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="none" />""#, at.0 * SVG_CELL_SIZE, at.1 * SVG_CELL_SIZE, SVG_CELL_SIZE, SVG_CELL_SIZE, color).unwrap();
-    };
-    writeln!(svg, r#"<svg viewBox="{} {} {} {}" xmlns="http://www.w3.org/2000/svg">"#,
-             -25 * SVG_CELL_SIZE, -25 * SVG_CELL_SIZE, 50 * SVG_CELL_SIZE, 50 * SVG_CELL_SIZE)
-        .unwrap();
-    writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="black" />"#,
-             -25 * SVG_CELL_SIZE, -25 * SVG_CELL_SIZE, 50 * SVG_CELL_SIZE, 50 * SVG_CELL_SIZE)
-        .unwrap();
-
-    for wall in walls
+    let mut result: Vec<(CoordType, &'static str)> = Vec::new();
+    result.extend(walls.iter().map(|wall| (*wall, palette::WHITE)));
+    result.extend(probed.iter().map(|site| (*site, palette::GREY)));
+    result.extend(path.iter().map(|site| (*site, palette::SKY)));
+    if let Some(point) = target
     {
-        drawBox(&mut svg, wall, "#ffffff");
+        result.push((*point, palette::GREEN));
     }
+    result.push((*pos, palette::RED));
+    result
+}
 
-    for site in probed
-    {
-        drawBox(&mut svg, site, "#a4b0be");
-    }
+fn drawState(pos: &CoordType, walls: &HashSet<CoordType>,
+             path: &Vec<CoordType>, target: &Option<CoordType>,
+             probed: &HashSet<CoordType>) -> Canvas
+{
+    let mut canvas = Canvas::new();
+    let corner = -25.0 * SVG_CELL_SIZE;
+    canvas.setViewBox(corner, corner, 50.0 * SVG_CELL_SIZE, 50.0 * SVG_CELL_SIZE);
+    canvas.rect(corner, corner, 50.0 * SVG_CELL_SIZE, 50.0 * SVG_CELL_SIZE, &Style::fill(palette::BLACK));
 
-    for site in path
+    for (site, color) in stateColors(pos, walls, path, target, probed)
     {
-        drawBox(&mut svg, site, "#1e90ff");
+        canvas.cell(site.0, site.1, SVG_CELL_SIZE, &Style::fill(color));
     }
+    canvas
+}
 
-    if let Some(point) = target
+fn drawStateScreen(pos: &CoordType, walls: &HashSet<CoordType>,
+                   path: &Vec<CoordType>, target: &Option<CoordType>,
+                   probed: &HashSet<CoordType>) -> Screen
+{
+    // What is known so far, and a little around it.
+    let bounds = Bounds::of(walls.iter().chain(&[*pos])).expanded(1);
+    let mut screen = Screen::new(bounds);
+    for (site, color) in stateColors(pos, walls, path, target, probed)
     {
-        drawBox(&mut svg, &point, "#2ed573");
+        screen.set(&site, Cell::block(color));
     }
-
-    drawBox(&mut svg, pos, "#ff4757");
-
-    writeln!(svg, "</svg>").unwrap();
+    screen.setStatus(&format!("Position: {:?}, path length: {}", pos, path.len()));
+    screen
 }
 
-
 fn step(core: &mut intcode::IntCodeComputer, dir: MoveDirection) -> Response
 {
     Response::from(core.pipe(Some(dir as intcode::ValueType)).unwrap()).unwrap()
@@ -274,129 +282,75 @@ enum TileType
 
 struct Field
 {
-    data: Vec<TileType>,
+    grid: Grid<TileType>,
     pub target: CoordType,
-    x_bound: (intcode::ValueType, intcode::ValueType),
-    y_bound: (intcode::ValueType, intcode::ValueType),
-}
-
-fn drawBox(f: &mut String, at: &CoordType, color: &str)
-{
-    writeln!(f, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="none" />"#, at.0 * SVG_CELL_SIZE, at.1 * SVG_CELL_SIZE, SVG_CELL_SIZE, SVG_CELL_SIZE, color).unwrap();
 }
 
 impl Field
 {
-    fn width(&self) -> usize
-    {
-        (self.x_bound.1 - self.x_bound.0) as usize
-    }
-
-    fn height(&self) -> usize
-    {
-        (self.y_bound.1 - self.y_bound.0) as usize
-    }
-
-    fn coord2Index(&self, coord: &CoordType) -> usize
-    {
-        (coord.1 - self.y_bound.0) as usize * self.width() + (coord.0 - self.x_bound.0) as usize
-    }
-
-    fn index2Coord(&self, i: usize) -> CoordType
-    {
-        ((i % self.width()) as intcode::ValueType + self.x_bound.0,
-         (i / self.width()) as intcode::ValueType + self.y_bound.0)
-    }
-
     pub fn at(&self, coord: &CoordType) -> TileType
     {
-        self.data[self.coord2Index(coord)]
-    }
-
-    pub fn svgViewBox(&self) -> String
-    {
-        format!(r#"viewBox="{} {} {} {}""#,
-                self.x_bound.0 * SVG_CELL_SIZE, self.y_bound.0 * SVG_CELL_SIZE,
-                (self.width() + 1) * SVG_CELL_SIZE as usize, (self.height() + 1) * SVG_CELL_SIZE as usize)
-    }
-
-    pub fn svgBg(&self) -> String
-    {
-        format!(r#"<rect x="{}" y="{}" width="{}" height="{}" fill="black" Stoke="none" />"#,
-                self.x_bound.0 * SVG_CELL_SIZE, self.y_bound.0 * SVG_CELL_SIZE,
-                self.width() * SVG_CELL_SIZE as usize, self.height() * SVG_CELL_SIZE as usize)
+        *self.grid.get(coord).unwrap()
     }
 
-    pub fn drawBody(&self) -> String
+    // Draw the walls, the unknown and the target onto a new canvas.
+    pub fn drawBody(&self) -> Canvas
     {
-        let mut svg = String::new();
-        for i in 0..self.data.len()
+        let bounds = &self.grid.bounds;
+        let mut canvas = Canvas::new();
+        canvas.setViewBox(bounds.x.0 as f64 * SVG_CELL_SIZE, bounds.y.0 as f64 * SVG_CELL_SIZE,
+                          (bounds.width() + 1) as f64 * SVG_CELL_SIZE,
+                          (bounds.height() + 1) as f64 * SVG_CELL_SIZE);
+        canvas.rect(bounds.x.0 as f64 * SVG_CELL_SIZE, bounds.y.0 as f64 * SVG_CELL_SIZE,
+                    bounds.width() as f64 * SVG_CELL_SIZE, bounds.height() as f64 * SVG_CELL_SIZE,
+                    &Style::fill(palette::BLACK));
+
+        for (coord, tile) in self.grid.iter()
         {
-            let coord = self.index2Coord(i);
             if coord == self.target
             {
-                drawBox(&mut svg, &coord, "#2ed573");
+                canvas.cell(coord.0, coord.1, SVG_CELL_SIZE, &Style::fill(palette::GREEN));
             }
-            else if self.data[i] == TileType::Wall || self.data[i] == TileType::Unknown
+            else if *tile == TileType::Wall || *tile == TileType::Unknown
             {
-                drawBox(&mut svg, &coord, "white");
+                canvas.cell(coord.0, coord.1, SVG_CELL_SIZE, &Style::fill(palette::WHITE));
             }
         }
-        svg
+        canvas
     }
 
-    pub fn drawFieldWithAir(&self, aired: &HashSet<CoordType>) -> String
+    pub fn drawFieldWithAir(&self, aired: &HashSet<CoordType>) -> Canvas
     {
-        let mut svg = String::new();
-        writeln!(svg, r#"<svg {} xmlns="http://www.w3.org/2000/svg">"#, self.svgViewBox()).unwrap();
-        writeln!(svg, "{}", self.svgBg()).unwrap();
-        writeln!(svg, "{}", self.drawBody()).unwrap();
-
+        let mut canvas = self.drawBody();
+        canvas.layer("air");
         for air in aired
         {
-            drawBox(&mut svg, air, "#a4b0be");
+            canvas.cell(air.0, air.1, SVG_CELL_SIZE, &Style::fill(palette::GREY));
         }
-
-        writeln!(svg, "</svg>").unwrap();
-        svg
+        canvas
     }
 
     pub fn from(walls: &HashSet<CoordType>, probed: &HashSet<CoordType>, target: &CoordType) -> Self
     {
+        // The droid starts at the origin.
+        let bounds = Bounds::of(walls.iter().chain(&[(0, 0)]));
         let mut result = Field
         {
-            data: vec![],
-            x_bound: (0, 0),
-            y_bound: (0, 0),
+            grid: Grid::new(bounds, TileType::Unknown),
             target: target.clone(),
         };
 
         for coord in walls
         {
-            result.x_bound.0 = coord.0.min(result.x_bound.0);
-            result.x_bound.1 = coord.0.max(result.x_bound.1);
-            result.y_bound.0 = coord.1.min(result.y_bound.0);
-            result.y_bound.1 = coord.1.max(result.y_bound.1);
-        }
-        result.x_bound.1 += 1;
-        result.y_bound.1 += 1;
-
-        result.data.resize(result.width() * result.height(), TileType::Unknown);
-
-        for coord in walls
-        {
-            let index = result.coord2Index(&coord);
-            result.data[index] = TileType::Wall;
+            result.grid.set(coord, TileType::Wall);
         }
 
         for coord in probed
         {
-            let index = result.coord2Index(&coord);
-            result.data[index] = TileType::Empty;
+            result.grid.set(coord, TileType::Empty);
         }
 
-        let index = result.coord2Index(target);
-        result.data[index] = TileType::Empty;
+        result.grid.set(target, TileType::Empty);
         result
     }
 
@@ -412,53 +366,20 @@ impl Field
         let mut to_check: HashSet<CoordType> = HashSet::new();
         to_check.insert(self.target.clone());
         let mut time: usize = 0;
-
-        let svg_dir = Path::new("frames");
-        if !svg_dir.exists()
-        {
-            fs::create_dir_all(svg_dir).unwrap();
-        }
+        let mut animation = vis::Animation::new("15-air");
 
         loop
         {
-            let mut svg = fs::File::create(svg_dir.join(format!("{:03}.svg", time))).unwrap();
-            write!(svg, "{}", self.drawFieldWithAir(&aired)).unwrap();
+            animation.frame(|| self.drawFieldWithAir(&aired));
 
             let mut next_checks: HashSet<CoordType> = HashSet::new();
             for source in &to_check
             {
                 aired.insert(source.clone());
-                if source.0 > self.x_bound.0
-                {
-                    let maybe = (source.0 - 1, source.1);
-                    if airNext(&maybe, self, &to_check, &aired)
-                    {
-                        next_checks.insert(maybe);
-                    }
-                }
-
-                if source.1 > self.y_bound.0
+                for dir in &Direction::ALL
                 {
-                    let maybe = (source.0, source.1 - 1);
-                    if airNext(&maybe, self, &to_check, &aired)
-                    {
-                        next_checks.insert(maybe);
-                    }
-                }
-
-                if source.0 < self.x_bound.1 - 1
-                {
-                    let maybe = (source.0 + 1, source.1);
-                    if airNext(&maybe, self, &to_check, &aired)
-                    {
-                        next_checks.insert(maybe);
-                    }
-                }
-
-                if source.1 < self.y_bound.1 - 1
-                {
-                    let maybe = (source.0, source.1 + 1);
-                    if airNext(&maybe, self, &to_check, &aired)
+                    let maybe = dir.step(source);
+                    if self.grid.bounds.contains(&maybe) && airNext(&maybe, self, &to_check, &aired)
                     {
                         next_checks.insert(maybe);
                     }
@@ -467,6 +388,7 @@ impl Field
 
             if next_checks.is_empty()
             {
+                animation.finish();
                 return time;
             }
             else
@@ -485,21 +407,15 @@ fn findPathLength(core: &mut intcode::IntCodeComputer) -> (Field, usize)
     let mut probed: HashSet<CoordType> = HashSet::new();
     let mut walls: HashSet<CoordType> = HashSet::new();
     let mut min_length = usize::max_value();
-    #[allow(unused_variables)]
-    let mut frame_idx = 0;
     let mut target: Option<CoordType> = None;
     let mut state = BotState::Forward;
-
-    let svg_dir = Path::new("frames");
-    if !svg_dir.exists()
-    {
-        fs::create_dir_all(svg_dir).unwrap();
-    }
+    let mut live = term::Live::new();
+    let mut animation = vis::Animation::new("15-path");
 
     loop
     {
-        // drawState(&svg_dir.join(format!("{:05}.svg", frame_idx)),
-        //           &pos, &walls, &path, &target, &probed);
+        animation.frame(|| drawState(&pos, &walls, &path, &target, &probed));
+        live.frame(|| drawStateScreen(&pos, &walls, &path, &target, &probed));
 
         match state
         {
@@ -532,6 +448,8 @@ fn findPathLength(core: &mut intcode::IntCodeComputer) -> (Field, usize)
                 {
                     // No path.
                     let field = Field::from(&walls, &probed, &target.unwrap());
+                    live.finish();
+                    animation.finish();
                     return (field, min_length);
                 }
 
@@ -542,7 +460,6 @@ fn findPathLength(core: &mut intcode::IntCodeComputer) -> (Field, usize)
                 state = BotState::Forward;
             },
         }
-        frame_idx += 1;
     }
 }
 
@@ -562,3 +479,11 @@ pub fn part2(input: &str) -> usize
     let (field, _) = findPathLength(&mut computer);
     field.diffuseTime()
 }
+
+pub const DAY: Day = Day
+{
+    num: 15,
+    title: "Oxygen System",
+    input: Input::Trimmed,
+    parts: &[solver!(part1), solver!(part2)],
+};
@@ -0,0 +1,112 @@
+use std::fs;
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+// Where the puzzle inputs are, by default.
+pub const DIR: &str = "inputs";
+
+// The day number at the start of a file name, after an optional
+// “day”, like in “08.txt”, “8.txt”, “day08.txt” or “08-int.txt”. Also
+// whether that is all there is to the name, besides the extension.
+fn dayOfName(name: &str) -> Option<(u8, bool)>
+{
+    let stem = name.split('.').next().unwrap_or(name);
+    let rest = stem.strip_prefix("day").unwrap_or(stem);
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let day = digits.parse::<u8>().ok()?;
+    Some((day, digits.len() == rest.len()))
+}
+
+// All the inputs of `day' in `dir', with the main input first and
+// then alternates like “08-int.txt” by name.
+pub fn inputsOf(dir: &Path, day: u8) -> Result<Vec<PathBuf>, String>
+{
+    let entries = fs::read_dir(dir)
+        .map_err(|_| format!("Failed to read directory {}.", dir.display()))?;
+    let mut found: Vec<(bool, PathBuf)> = vec![];
+    for entry in entries
+    {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let name = match path.file_name().and_then(|name| name.to_str())
+        {
+            Some(name) => String::from(name),
+            None => continue,
+        };
+        if let Some((num, main)) = dayOfName(&name)
+        {
+            if num == day && path.is_file()
+            {
+                found.push((!main, path));
+            }
+        }
+    }
+    found.sort();
+    Ok(found.into_iter().map(|(_, path)| path).collect())
+}
+
+// The input of `day' in `dir'. That is the file named by just the day
+// number, or the only file for that day if there is no such file.
+pub fn find(dir: &Path, day: u8) -> Result<PathBuf, String>
+{
+    let inputs = inputsOf(dir, day)?;
+    let mains: Vec<&PathBuf> = inputs.iter().filter(
+        |path| path.file_name().and_then(|name| name.to_str())
+            .and_then(dayOfName).map_or(false, |(_, main)| main))
+        .collect();
+    match (mains.len(), inputs.len())
+    {
+        (1, _) => Ok(mains[0].clone()),
+        (0, 1) => Ok(inputs[0].clone()),
+        (_, 0) => Err(format!("No input for day {} in {}", day, dir.display())),
+        _ => Err(format!("Several inputs for day {} in {}: {}", day, dir.display(),
+                         inputs.iter().map(|path| path.display().to_string())
+                         .collect::<Vec<String>>().join(", "))),
+    }
+}
+
+// Read an input file, or stdin if `path' is “-”.
+pub fn read(path: &Path) -> Result<String, String>
+{
+    if path == Path::new("-")
+    {
+        let mut input = String::new();
+        io::stdin().lock().read_to_string(&mut input).map_err(|_| String::from("Failed to read input"))?;
+        return Ok(input);
+    }
+    fs::read_to_string(path).map_err(|_| format!("Failed to read file {}.", path.display()))
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testDayOfName()
+{
+    assert_eq!(dayOfName("08.txt"), Some((8, true)));
+    assert_eq!(dayOfName("8.txt"), Some((8, true)));
+    assert_eq!(dayOfName("day12.txt"), Some((12, true)));
+    assert_eq!(dayOfName("08-int.txt"), Some((8, false)));
+    assert_eq!(dayOfName("08"), Some((8, true)));
+    assert_eq!(dayOfName("README"), None);
+}
+
+#[test]
+fn testFind()
+{
+    let dir = std::env::temp_dir().join(format!("advent-inputs-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in ["08.txt", "08-int.txt", "3-example.txt", "day04.txt", "04-example.txt",
+                 "5-a.txt", "5-b.txt"]
+    {
+        fs::write(dir.join(name), name).unwrap();
+    }
+
+    assert_eq!(find(&dir, 8), Ok(dir.join("08.txt")));
+    assert_eq!(inputsOf(&dir, 8), Ok(vec![dir.join("08.txt"), dir.join("08-int.txt")]));
+    assert_eq!(find(&dir, 3), Ok(dir.join("3-example.txt")));
+    assert_eq!(find(&dir, 4), Ok(dir.join("day04.txt")));
+    assert!(find(&dir, 5).unwrap_err().starts_with("Several inputs for day 5"));
+    assert!(find(&dir, 6).unwrap_err().starts_with("No input for day 6"));
+    assert_eq!(read(&dir.join("08-int.txt")), Ok(String::from("08-int.txt")));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,107 @@
+use std::fmt::Display;
+
+use super::{day01, day02, day03, day04, day05, day06, day07, day09, day10, day11,
+            day12, day13, day15};
+
+// The answer of a part. Parts answer with numbers, strings or
+// pictures, so anything that can be printed will do.
+pub type Answer = Box<dyn Display + Send>;
+
+// Solve a part with the puzzle input.
+pub type Solver = fn(&str) -> Answer;
+
+// What a day does with its input before solving.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Input
+{
+    // Whitespace at both ends is removed.
+    Trimmed,
+    // Exactly what is in the file.
+    Raw,
+}
+
+// A solved day, as registered by its module.
+pub struct Day
+{
+    pub num: u8,
+    pub title: &'static str,
+    pub input: Input,
+    // Solvers of the parts, part 1 first.
+    pub parts: &'static [Solver],
+}
+
+// Turn a `partN' function returning any printable type into a
+// `Solver'.
+#[macro_export]
+macro_rules! solver
+{
+    ($part:path) => { |input: &str| -> $crate::advent::registry::Answer { Box::new($part(input)) } }
+}
+
+// Every day with a solution, in order.
+pub const DAYS: &[&Day] = &[
+    &day01::DAY, &day02::DAY, &day03::DAY, &day04::DAY, &day05::DAY, &day06::DAY,
+    &day07::DAY, &day09::DAY, &day10::DAY, &day11::DAY, &day12::DAY, &day13::DAY,
+    &day15::DAY,
+];
+
+impl Day
+{
+    pub fn prepare<'a>(&self, input: &'a str) -> &'a str
+    {
+        match self.input
+        {
+            Input::Trimmed => input.trim(),
+            Input::Raw => input,
+        }
+    }
+}
+
+pub fn find(num: u8) -> Option<&'static Day>
+{
+    DAYS.iter().find(|day| day.num == num).copied()
+}
+
+// The solver of a part of a day, or why there is none.
+pub fn solver(day: u8, part: u8) -> Result<Solver, String>
+{
+    let solved = find(day).ok_or(format!("Day {} is not implemented.", day))?;
+    part.checked_sub(1).and_then(|i| solved.parts.get(i as usize)).copied()
+        .ok_or(format!("Part {} of day {} is not implemented.", part, day))
+}
+
+// Run a part on the input as read from the file. The day decides
+// whether to trim it.
+pub fn run(day: u8, part: u8, input: &str) -> Result<String, String>
+{
+    let solve = solver(day, part)?;
+    Ok(solve(find(day).unwrap().prepare(input)).to_string())
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testDays()
+{
+    for pair in DAYS.windows(2)
+    {
+        assert!(pair[0].num < pair[1].num);
+    }
+    assert!(DAYS.iter().all(|day| !day.title.is_empty() && !day.parts.is_empty()));
+    assert_eq!(find(8).map(|day| day.num), None);
+    assert_eq!(find(13).map(|day| day.title), Some("Care Package"));
+}
+
+#[test]
+fn testRun()
+{
+    assert_eq!(run(1, 1, "12\n14"), Ok(String::from("4")));
+    assert_eq!(run(1, 1, "\n12\n14\n"), Ok(String::from("4")));
+    assert_eq!(run(1, 2, "1969"), Ok(String::from("966")));
+    assert_eq!(run(8, 1, ""), Err(String::from("Day 8 is not implemented.")));
+    assert_eq!(run(1, 3, ""), Err(String::from("Part 3 of day 1 is not implemented.")));
+    let raw = Day { num: 0, title: "", input: Input::Raw, parts: &[] };
+    assert_eq!(raw.prepare(" x\n"), " x\n");
+    assert_eq!(run(1, 0, ""), Err(String::from("Part 0 of day 1 is not implemented.")));
+}
@@ -0,0 +1,616 @@
+#![allow(dead_code)]
+
+// Static analysis of Intcode programs: decoding, basic blocks and the
+// control-flow graph, and a few things worth knowing when reverse
+// engineering a puzzle program.
+
+use std::vec::Vec;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{ArgMode, OpCode, ValueType};
+use super::assembly::SymbolTable;
+
+// Most relative writes listed in the report.
+const MAX_LISTED: usize = 5;
+
+// Where the code at `address' is, with its label if known.
+pub fn describeCode(address: usize, symbols: &Option<SymbolTable>) -> String
+{
+    match symbols
+    {
+        Some(table) if table.describeCode(address) != address.to_string() =>
+            format!("{} ({})", address, table.describeCode(address)),
+        _ => address.to_string(),
+    }
+}
+
+// The instruction at `address' in `mem', in inssembly-like syntax.
+pub fn disassemble(mem: &[ValueType], address: usize, symbols: &Option<SymbolTable>) -> String
+{
+    let code = match mem.get(address).map(|x| OpCode::fromInt(*x))
+    {
+        Some(Ok(code)) => code,
+        _ => { return format!("{}: ???", describeCode(address, symbols)); },
+    };
+
+    let args: Vec<String> = (0..code.arg_count as usize).map(
+        |i|
+        {
+            let value = match mem.get(address + i + 1)
+            {
+                Some(value) => *value,
+                None => { return String::from("???"); },
+            };
+            match code.arg_modes[i]
+            {
+                ArgMode::Position => match symbols
+                {
+                    Some(table) if value >= 0 => table.describeData(value as usize),
+                    _ => format!("[{}]", value),
+                },
+                ArgMode::Immediate =>
+                {
+                    let label = symbols.as_ref().filter(|_| value >= 0)
+                        .and_then(|table| table.label(value as usize));
+                    match label
+                    {
+                        Some(name) if isJump(&code) && i == 1 => format!(":{}", name),
+                        _ => value.to_string(),
+                    }
+                },
+                ArgMode::Relative => format!("[rb{:+}]", value),
+            }
+        }).collect();
+
+    if args.is_empty()
+    {
+        format!("{}: {}", describeCode(address, symbols), code.name())
+    }
+    else
+    {
+        format!("{}: {} {}", describeCode(address, symbols), code.name(), args.join(", "))
+    }
+}
+
+fn isJump(code: &OpCode) -> bool
+{
+    code.code == 5 || code.code == 6
+}
+
+// Index of the argument that is written to, if any.
+fn writtenArg(code: &OpCode) -> Option<usize>
+{
+    match code.code
+    {
+        1 | 2 | 7 | 8 => Some(2),
+        3 => Some(0),
+        _ => None,
+    }
+}
+
+// A decoded instruction.
+#[derive(Clone, Debug)]
+pub struct Instruction
+{
+    pub address: usize,
+    pub op: OpCode,
+    pub args: Vec<ValueType>,
+}
+
+impl Instruction
+{
+    pub fn decode(code: &[ValueType], address: usize) -> Option<Instruction>
+    {
+        let op = OpCode::fromInt(*code.get(address)?).ok()?;
+        let end = address + 1 + op.arg_count as usize;
+        if end > code.len()
+        {
+            return None;
+        }
+        Some(Instruction { address, op, args: code[address + 1..end].to_vec() })
+    }
+
+    // Number of cells, with the opcode.
+    pub fn size(&self) -> usize
+    {
+        self.args.len() + 1
+    }
+
+    pub fn end(&self) -> usize
+    {
+        self.address + self.size()
+    }
+
+    // The condition of a jump, if it is known without running.
+    fn constantCondition(&self) -> Option<bool>
+    {
+        match self.op.arg_modes[0]
+        {
+            ArgMode::Immediate => Some(self.args[0] != 0),
+            _ => None,
+        }
+    }
+
+    // Target of a jump, if it is an immediate address.
+    fn target(&self) -> Option<usize>
+    {
+        match self.op.arg_modes[1]
+        {
+            ArgMode::Immediate if self.args[1] >= 0 => Some(self.args[1] as usize),
+            _ => None,
+        }
+    }
+}
+
+// How control gets from one block to another.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edge
+{
+    // The block ends without jumping, or the jump is not taken.
+    Fall,
+    Jump,
+}
+
+#[derive(Clone, Debug)]
+pub struct Block
+{
+    pub start: usize,
+    // One past the last cell of the last instruction.
+    pub end: usize,
+    pub instructions: Vec<Instruction>,
+    pub successors: Vec<(usize, Edge)>,
+    // Ends in a jump whose target is only known at run time.
+    pub indirect: bool,
+    // Ends in something that is not a valid instruction.
+    pub invalid: bool,
+}
+
+// A write that changes code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeWrite
+{
+    // Address of the writing instruction.
+    pub writer: usize,
+    // The cell written to.
+    pub target: usize,
+}
+
+pub struct Analysis
+{
+    pub code: Vec<ValueType>,
+    pub entry: usize,
+    // Every instruction reachable from the entry, by address.
+    pub instructions: BTreeMap<usize, Instruction>,
+    pub blocks: BTreeMap<usize, Block>,
+    // Ranges of cells that are never reached as code. They may just as
+    // well be data.
+    pub unreachable: Vec<(usize, usize)>,
+    // Writes into reachable code.
+    pub code_writes: Vec<CodeWrite>,
+    // Addresses of instructions that write relative to the relative
+    // base. Where they write is only known at run time, so they are
+    // not in `code_writes'.
+    pub relative_writes: Vec<usize>,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+}
+
+// Analyze `code' starting at `entry'.
+//
+// Only jumps with immediate targets can be followed. Code that is only
+// reached through other jumps, like the return from a function, would
+// be lost, so values that are written with `add X, 0, ...',
+// `mult 1, X, ...' and the like are also tried as entries if they point to an
+// instruction, since that is how return addresses are pushed.
+pub fn analyze(code: &[ValueType], entry: usize) -> Analysis
+{
+    let mut instructions: BTreeMap<usize, Instruction> = BTreeMap::new();
+    let mut leaders: BTreeSet<usize> = BTreeSet::new();
+    let mut invalid: BTreeSet<usize> = BTreeSet::new();
+    let mut todo: Vec<usize> = vec![entry];
+    leaders.insert(entry);
+
+    let mut trace = |todo: &mut Vec<usize>, instructions: &mut BTreeMap<usize, Instruction>,
+                     leaders: &mut BTreeSet<usize>|
+    {
+        while let Some(start) = todo.pop()
+        {
+            let mut address = start;
+            while !instructions.contains_key(&address)
+            {
+                let instruction = match Instruction::decode(code, address)
+                {
+                    Some(instruction) => instruction,
+                    None => { invalid.insert(address); break; },
+                };
+                let next = instruction.end();
+                let op = instruction.op.code;
+                let condition = instruction.constantCondition();
+                let target = instruction.target();
+                instructions.insert(address, instruction);
+
+                if op == 99
+                {
+                    break;
+                }
+                if op == 5 || op == 6
+                {
+                    let taken = condition.map(|c| c == (op == 5));
+                    if taken != Some(false)
+                    {
+                        if let Some(target) = target
+                        {
+                            leaders.insert(target);
+                            todo.push(target);
+                        }
+                    }
+                    if taken == Some(true)
+                    {
+                        break;
+                    }
+                    leaders.insert(next);
+                }
+                address = next;
+            }
+        }
+    };
+
+    trace(&mut todo, &mut instructions, &mut leaders);
+
+    // Return addresses.
+    loop
+    {
+        let returns: Vec<usize> = instructions.values().filter_map(
+            |instruction|
+            {
+                let op = &instruction.op;
+                let neutral = match op.code { 1 => 0, 2 => 1, _ => { return None; } };
+                if (op.arg_modes[0], op.arg_modes[1]) != (ArgMode::Immediate, ArgMode::Immediate)
+                {
+                    return None;
+                }
+                let value = match (instruction.args[0], instruction.args[1])
+                {
+                    (value, x) if x == neutral => value,
+                    (x, value) if x == neutral => value,
+                    _ => { return None; },
+                };
+                if value >= 0 { Some(value as usize) } else { None }
+            })
+            .filter(|address| !instructions.contains_key(address)
+                    && Instruction::decode(code, *address).is_some()
+                    && !overlaps(&instructions, *address))
+            .collect();
+        if returns.is_empty()
+        {
+            break;
+        }
+        for address in returns
+        {
+            leaders.insert(address);
+            todo.push(address);
+        }
+        trace(&mut todo, &mut instructions, &mut leaders);
+    }
+
+    let blocks = buildBlocks(&instructions, &leaders, &invalid);
+    let unreachable = unreachableRanges(code, &instructions);
+
+    let mut code_writes = vec![];
+    let mut relative_writes = vec![];
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    for instruction in instructions.values()
+    {
+        match instruction.op.code
+        {
+            3 => { inputs.push(instruction.address); },
+            4 => { outputs.push(instruction.address); },
+            _ => {},
+        }
+
+        if let Some(i) = writtenArg(&instruction.op)
+        {
+            let target = match instruction.op.arg_modes[i]
+            {
+                ArgMode::Position if instruction.args[i] >= 0 => instruction.args[i] as usize,
+                ArgMode::Position => { continue; },
+                ArgMode::Immediate => instruction.address + i + 1,
+                ArgMode::Relative =>
+                {
+                    relative_writes.push(instruction.address);
+                    continue;
+                },
+            };
+            if overlaps(&instructions, target)
+            {
+                code_writes.push(CodeWrite { writer: instruction.address, target });
+            }
+        }
+    }
+
+    Analysis
+    {
+        code: code.to_vec(),
+        entry,
+        instructions,
+        blocks,
+        unreachable,
+        code_writes,
+        relative_writes,
+        inputs,
+        outputs,
+    }
+}
+
+// Whether `address' is part of one of `instructions'.
+fn overlaps(instructions: &BTreeMap<usize, Instruction>, address: usize) -> bool
+{
+    instructions.range(..=address).next_back()
+        .is_some_and(|(_, instruction)| address < instruction.end())
+}
+
+fn buildBlocks(instructions: &BTreeMap<usize, Instruction>, leaders: &BTreeSet<usize>,
+               invalid: &BTreeSet<usize>) -> BTreeMap<usize, Block>
+{
+    let mut blocks: BTreeMap<usize, Block> = BTreeMap::new();
+    let mut current: Option<Block> = None;
+
+    for (address, instruction) in instructions
+    {
+        let starts_block = match &current
+        {
+            None => true,
+            Some(block) => block.end != *address || leaders.contains(address),
+        };
+        if starts_block
+        {
+            if let Some(mut block) = current.take()
+            {
+                // Fell into the next leader.
+                if block.end == *address
+                {
+                    block.successors.push((*address, Edge::Fall));
+                }
+                else
+                {
+                    block.invalid = invalid.contains(&block.end);
+                }
+                blocks.insert(block.start, block);
+            }
+            current = Some(Block
+            {
+                start: *address,
+                end: *address,
+                instructions: vec![],
+                successors: vec![],
+                indirect: false,
+                invalid: false,
+            });
+        }
+
+        let block = current.as_mut().unwrap();
+        block.instructions.push(instruction.clone());
+        block.end = instruction.end();
+
+        let op = instruction.op.code;
+        if op == 99
+        {
+            blocks.insert(block.start, current.take().unwrap());
+        }
+        else if op == 5 || op == 6
+        {
+            let taken = instruction.constantCondition().map(|c| c == (op == 5));
+            if taken != Some(false)
+            {
+                match instruction.target()
+                {
+                    Some(target) => { block.successors.push((target, Edge::Jump)); },
+                    None => { block.indirect = true; },
+                }
+            }
+            if taken != Some(true)
+            {
+                block.successors.push((instruction.end(), Edge::Fall));
+            }
+            blocks.insert(block.start, current.take().unwrap());
+        }
+    }
+
+    if let Some(mut block) = current
+    {
+        block.invalid = invalid.contains(&block.end);
+        blocks.insert(block.start, block);
+    }
+    blocks
+}
+
+fn unreachableRanges(code: &[ValueType], instructions: &BTreeMap<usize, Instruction>)
+    -> Vec<(usize, usize)>
+{
+    let mut covered = vec![false; code.len()];
+    for instruction in instructions.values()
+    {
+        let end = instruction.end().min(code.len());
+        covered[instruction.address..end].iter_mut().for_each(|cell| *cell = true);
+    }
+
+    let mut ranges = vec![];
+    let mut i = 0;
+    while i < code.len()
+    {
+        if covered[i]
+        {
+            i += 1;
+            continue;
+        }
+        let begin = i;
+        while i < code.len() && !covered[i]
+        {
+            i += 1;
+        }
+        ranges.push((begin, i));
+    }
+    ranges
+}
+
+impl Analysis
+{
+    // The control-flow graph in Graphviz dot.
+    pub fn toDot(&self, symbols: &Option<SymbolTable>) -> String
+    {
+        let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut lines = vec![String::from("digraph intcode {"),
+                             String::from("    node [shape=box, fontname=monospace];")];
+
+        for block in self.blocks.values()
+        {
+            let mut text: Vec<String> = block.instructions.iter()
+                .map(|instruction| escape(disassemble(&self.code, instruction.address, symbols)))
+                .collect();
+            if block.invalid
+            {
+                text.push(format!("{}: ???", block.end));
+            }
+            let entry = if block.start == self.entry { ", penwidth=2" } else { "" };
+            lines.push(format!("    b{} [label=\"{}\\l\"{}];", block.start, text.join("\\l"), entry));
+
+            for (target, edge) in &block.successors
+            {
+                let style = match edge
+                {
+                    Edge::Fall => "",
+                    Edge::Jump => " [style=bold]",
+                };
+                lines.push(format!("    b{} -> b{}{};", block.start, target, style));
+            }
+            if block.indirect
+            {
+                lines.push(format!("    b{} -> unknown [style=dashed];", block.start));
+            }
+        }
+
+        if self.blocks.values().any(|block| block.indirect)
+        {
+            lines.push(String::from("    unknown [label=\"?\", shape=circle];"));
+        }
+        lines.push(String::from("}"));
+        lines.join("\n") + "\n"
+    }
+
+    // A summary for people.
+    pub fn report(&self, symbols: &Option<SymbolTable>) -> String
+    {
+        let describe = |address: usize| describeCode(address, symbols);
+        let list = |addresses: &Vec<usize>| if addresses.is_empty()
+        {
+            String::from("none")
+        }
+        else
+        {
+            addresses.iter().map(|a| describe(*a)).collect::<Vec<String>>().join(", ")
+        };
+
+        let mut lines = vec![];
+        lines.push(format!("{} instructions in {} blocks, entry at {}.",
+                           self.instructions.len(), self.blocks.len(), describe(self.entry)));
+        let indirect: Vec<usize> = self.blocks.values().filter(|b| b.indirect)
+            .map(|b| b.instructions.last().unwrap().address).collect();
+        lines.push(format!("Indirect jumps: {}", list(&indirect)));
+        lines.push(format!("Inputs: {}", list(&self.inputs)));
+        lines.push(format!("Outputs: {}", list(&self.outputs)));
+
+        lines.push(String::from("Unreachable:"));
+        for (begin, end) in &self.unreachable
+        {
+            lines.push(format!("    {}..{}", describe(*begin), describe(*end)));
+        }
+
+        lines.push(String::from("Writes that modify code:"));
+        for write in &self.code_writes
+        {
+            lines.push(format!("    {} writes to {}", disassemble(&self.code, write.writer, symbols),
+                               describe(write.target)));
+        }
+        // There are a lot of these in programs with a stack, so only
+        // the first few are listed.
+        let mut relative: Vec<String> = self.relative_writes.iter().take(MAX_LISTED)
+            .map(|a| describe(*a)).collect();
+        if self.relative_writes.len() > MAX_LISTED
+        {
+            relative.push(String::from("..."));
+        }
+        lines.push(format!("Writes relative to the base, not checked: {}{}",
+                           self.relative_writes.len(),
+                           if relative.is_empty() { String::new() }
+                           else { format!(" ({})", relative.join(", ")) }));
+        lines.join("\n")
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testBlocks()
+{
+    // input x; loop: add x, -1, x; jmpt x, :loop; output x; halt
+    let code = vec![3,12,1001,12,-1,12,1005,12,2,4,12,99,0];
+    let analysis = analyze(&code, 0);
+    assert_eq!(analysis.blocks.keys().cloned().collect::<Vec<usize>>(), vec![0, 2, 9]);
+    assert_eq!(analysis.blocks[&0].successors, vec![(2, Edge::Fall)]);
+    assert_eq!(analysis.blocks[&2].successors, vec![(2, Edge::Jump), (9, Edge::Fall)]);
+    assert!(analysis.blocks[&9].successors.is_empty());
+    assert_eq!(analysis.inputs, vec![0]);
+    assert_eq!(analysis.outputs, vec![9]);
+    assert_eq!(analysis.unreachable, vec![(12, 13)]);
+    assert!(analysis.code_writes.is_empty());
+
+    let dot = analysis.toDot(&None);
+    assert!(dot.contains("b2 -> b2 [style=bold];"));
+    assert!(dot.contains("b2 -> b9;"));
+}
+
+#[test]
+fn testConstantJumps()
+{
+    // jmpt 1, 7; then dead code; 7: halt
+    let code = vec![1105,1,7,104,1,99,0,99];
+    let analysis = analyze(&code, 0);
+    assert_eq!(analysis.blocks[&0].successors, vec![(7, Edge::Jump)]);
+    assert_eq!(analysis.unreachable, vec![(3, 7)]);
+
+    // A function call: push the return address 9, jump to 11, which
+    // returns through the stack.
+    let code = vec![21101,9,0,0,1105,1,11,0,0,104,0,2106,0,0];
+    let analysis = analyze(&code, 0);
+    assert!(analysis.instructions.contains_key(&9));
+    assert!(analysis.blocks[&11].indirect);
+    assert!(analysis.code_writes.is_empty());
+    assert_eq!(analysis.relative_writes, vec![0]);
+    assert!(analysis.report(&None).ends_with("Writes relative to the base, not checked: 1 (0)"));
+}
+
+#[test]
+fn testSelfModification()
+{
+    // Write 99 over the output.
+    let code = vec![1101,99,0,4,4,0,99];
+    let analysis = analyze(&code, 0);
+    assert_eq!(analysis.code_writes, vec![CodeWrite { writer: 0, target: 4 }]);
+    assert!(!analysis.blocks[&0].invalid);
+
+    let code = vec![1101,1,1,0,42];
+    let analysis = analyze(&code, 0);
+    assert!(analysis.blocks[&0].invalid);
+    assert!(analysis.toDot(&None).contains("4: ???"));
+}
+
+#[test]
+fn testDisassemble()
+{
+    let code = vec![1001,12,-1,12,99,22201];
+    assert_eq!(disassemble(&code, 0, &None), "0: add [12], -1, [12]");
+    assert_eq!(disassemble(&code, 4, &None), "4: halt");
+    assert_eq!(disassemble(&code, 5, &None), "5: add ???, ???, ???");
+    assert_eq!(disassemble(&code, 6, &None), "6: ???");
+}
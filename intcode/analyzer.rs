@@ -0,0 +1,104 @@
+#![allow(non_snake_case)]
+
+use std::vec::Vec;
+use std::env;
+use std::process::exit;
+use std::fs;
+
+use advent_2019::intcode::{image, analysis};
+
+use image::Image;
+
+fn usage(name: &String)
+{
+    println!("Usage: {name} [OPTIONS] FILE
+
+Analyze the Intcode program in FILE without running it, and print
+what was found: the inputs and outputs, unreachable code, and writes
+that may modify code. FILE can be Intcode text, a binary image, or
+inssembly source.
+
+Options:
+  -d, --dot OUTPUT         Write the control-flow graph to OUTPUT in
+                           Graphviz dot.
+  -e, --entry ADDR         Start from ADDR instead of the entry point
+                           of the program.
+  -S, --symbols SYMBOLS    Load labels and variable names from the
+                           symbol file SYMBOLS.
+  -h, --help               Show this message.", name=name);
+}
+
+fn main() -> Result<(), String>
+{
+    let args: Vec<String> = env::args().collect();
+    let mut dot_file: Option<&String> = None;
+    let mut entry: Option<usize> = None;
+    let mut symbol_file: Option<&String> = None;
+    let mut filename: Option<&String> = None;
+
+    let mut i = 1;
+    while i < args.len()
+    {
+        let has_value = i + 1 < args.len();
+        match &args[i][..]
+        {
+            "-h" | "--help" =>
+            {
+                usage(&args[0]);
+                return Ok(());
+            },
+            "-d" | "--dot" if has_value =>
+            {
+                i += 1;
+                dot_file = Some(&args[i]);
+            },
+            "-e" | "--entry" if has_value =>
+            {
+                i += 1;
+                entry = Some(args[i].parse().map_err(|_| format!("Invalid address: {}", args[i]))?);
+            },
+            "-S" | "--symbols" if has_value =>
+            {
+                i += 1;
+                symbol_file = Some(&args[i]);
+            },
+            arg if !arg.starts_with('-') && filename.is_none() => { filename = Some(&args[i]); },
+            _ =>
+            {
+                usage(&args[0]);
+                exit(1);
+            },
+        }
+        i += 1;
+    }
+
+    let filename = match filename
+    {
+        Some(filename) => filename,
+        None =>
+        {
+            usage(&args[0]);
+            exit(1);
+        },
+    };
+
+    let bytes = fs::read(filename).map_err(|_| format!("Failed to read file {}.", filename))?;
+    let mut image = Image::loadFile(filename, &bytes, false)?;
+
+    if let Some(filename) = symbol_file
+    {
+        let text = fs::read_to_string(filename)
+            .map_err(|_| format!("Failed to read file {}.", filename))?;
+        image.symbols = Some(text.parse()?);
+    }
+
+    let result = analysis::analyze(&image.code, entry.unwrap_or(image.entry));
+    println!("{}", result.report(&image.symbols));
+
+    if let Some(filename) = dot_file
+    {
+        fs::write(filename, result.toDot(&image.symbols))
+            .map_err(|_| format!("Failed to write file {}.", filename))?;
+    }
+    Ok(())
+}
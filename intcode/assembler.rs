@@ -2,22 +2,108 @@
 
 use std::io::{self, prelude::*};
 use std::vec::Vec;
+use std::env;
+use std::process::exit;
+use std::fs;
 
-mod assembly;
-mod intcode;
+use advent_2019::intcode::image;
+
+use image::Image;
+
+fn usage(name: &String)
+{
+    println!("Usage: {name} [-s SYMBOL_FILE] [-b]
+       {name} -c
+
+Read inssembly code from stdin, and write the assembled Intcode to
+stdout.
+
+Options:
+  -s, --symbols SYMBOL_FILE   Also write the addresses of all labels
+                              and variables to SYMBOL_FILE, for the
+                              emulator.
+  -b, --binary                Write a binary image instead of text. It
+                              includes the symbols and source line
+                              numbers.
+  -c, --convert               Read Intcode instead of inssembly, and
+                              write it in the other format: text
+                              becomes a binary image, and an image
+                              becomes text.
+  -h, --help                  Show this message.", name=name);
+}
 
 fn main() -> Result<(), String>
 {
-    let mut input_raw = String::new();
-    io::stdin().lock().read_to_string(&mut input_raw)
+    let args: Vec<String> = env::args().collect();
+    let mut symbol_file: Option<&String> = None;
+    let mut binary = false;
+    let mut convert = false;
+    let mut i = 1;
+    while i < args.len()
+    {
+        match &args[i][..]
+        {
+            "-h" | "--help" =>
+            {
+                usage(&args[0]);
+                return Ok(());
+            },
+            "-s" | "--symbols" if i + 1 < args.len() =>
+            {
+                i += 1;
+                symbol_file = Some(&args[i]);
+            },
+            "-b" | "--binary" => { binary = true; },
+            "-c" | "--convert" => { convert = true; },
+            _ =>
+            {
+                usage(&args[0]);
+                exit(1);
+            },
+        }
+        i += 1;
+    }
+
+    let mut input_raw: Vec<u8> = vec![];
+    io::stdin().lock().read_to_end(&mut input_raw)
         .map_err(|_| String::from("Failed to read input"))?;
-    let input = input_raw.trim();
 
-    let statements = assembly::parse(&input[..])?;
-    let code = assembly::assemble(&statements)?;
+    if convert
+    {
+        let image = Image::load(&input_raw)?;
+        if image::isImage(&input_raw)
+        {
+            println!("{}", image.toText());
+        }
+        else
+        {
+            io::stdout().write_all(&image.toBytes())
+                .map_err(|_| String::from("Failed to write output"))?;
+        }
+        return Ok(());
+    }
+
+    let input = String::from_utf8(input_raw)
+        .map_err(|_| String::from("Input is not text"))?;
+    let image = Image::assemble(&input)?;
+
+    if let Some(filename) = symbol_file
+    {
+        if let Some(symbols) = &image.symbols
+        {
+            fs::write(filename, symbols.to_string())
+                .map_err(|_| format!("Failed to write file {}.", filename))?;
+        }
+    }
 
-    let output: String = code.iter().map(|x| x.to_string())
-        .collect::<Vec<String>>().join(",");
-    println!("{}", output);
+    if binary
+    {
+        io::stdout().write_all(&image.toBytes())
+            .map_err(|_| String::from("Failed to write output"))?;
+    }
+    else
+    {
+        println!("{}", image.toText());
+    }
     Ok(())
 }
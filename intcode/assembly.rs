@@ -5,6 +5,115 @@ use std::collections::HashMap;
 
 use crate::intcode;
 
+// Addresses of the labels and variables in an assembled program. It
+// can be written to and read from a symbol file, which has one symbol
+// per line, as “label NAME ADDRESS” or “var NAME ADDRESS”.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct SymbolTable
+{
+    pub labels: HashMap<String, usize>,
+    pub variables: HashMap<String, usize>,
+}
+
+impl SymbolTable
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    #[allow(dead_code)]
+    pub fn label(&self, address: usize) -> Option<&str>
+    {
+        self.labels.iter().filter(|(_, addr)| **addr == address)
+            .map(|(name, _)| &name[..]).min()
+    }
+
+    #[allow(dead_code)]
+    pub fn variable(&self, address: usize) -> Option<&str>
+    {
+        self.variables.iter().filter(|(_, addr)| **addr == address)
+            .map(|(name, _)| &name[..]).min()
+    }
+
+    // Describe a code address, as “label” or “label+offset” from the
+    // closest label before it. Just the number if there is no such
+    // label.
+    #[allow(dead_code)]
+    pub fn describeCode(&self, address: usize) -> String
+    {
+        let closest = self.labels.iter().filter(|(_, addr)| **addr <= address)
+            .max_by(|(name1, addr1), (name2, addr2)|
+                    addr1.cmp(addr2).then(name2.cmp(name1)));
+        match closest
+        {
+            Some((name, addr)) if *addr == address => name.clone(),
+            Some((name, addr)) => format!("{}+{}", name, address - addr),
+            None => address.to_string(),
+        }
+    }
+
+    // Describe a data address, as a variable name if there is one.
+    #[allow(dead_code)]
+    pub fn describeData(&self, address: usize) -> String
+    {
+        match self.variable(address)
+        {
+            Some(name) => String::from(name),
+            None => format!("[{}]", address),
+        }
+    }
+}
+
+impl fmt::Display for SymbolTable
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let mut symbols: Vec<(&str, &String, &usize)> =
+            self.labels.iter().map(|(name, addr)| ("label", name, addr))
+            .chain(self.variables.iter().map(|(name, addr)| ("var", name, addr)))
+            .collect();
+        symbols.sort_by(|a, b| a.2.cmp(b.2).then(a.1.cmp(b.1)));
+        for (kind, name, addr) in symbols
+        {
+            writeln!(f, "{} {} {}", kind, name, addr)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SymbolTable
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String>
+    {
+        let mut table = SymbolTable::new();
+        for line in s.lines()
+        {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty()
+            {
+                continue;
+            }
+            if parts.len() != 3
+            {
+                return Err(format!("Invalid symbol: {}", line));
+            }
+
+            let addr: usize = parts[2].parse()
+                .map_err(|_| format!("Invalid symbol address: {}", line))?;
+            match parts[0]
+            {
+                "label" => { table.labels.insert(String::from(parts[1]), addr); },
+                "var" => { table.variables.insert(String::from(parts[1]), addr); },
+                _ => { return Err(format!("Invalid symbol type: {}", line)); },
+            }
+        }
+        Ok(table)
+    }
+}
+
 enum ParserState
 {
     Begin,
@@ -49,10 +158,9 @@ impl FromStr for Argument
         {
             Ok(Argument::Immediate(num))
         }
-        else if s.starts_with(":")
+        else if let Some(name) = s.strip_prefix(':')
         {
-            let name: String = s[1..].to_string();
-            Ok(Argument::Label(name))
+            Ok(Argument::Label(name.to_string()))
         }
         else
         {
@@ -82,10 +190,15 @@ pub struct Statement
     address: usize,
     arguments: Vec<Argument>,
     head: String,
+    // Line in the source, counted from 1.
+    line: usize,
 }
 
 impl Statement
 {
+    // The number of cells the statement assembles to. Nothing is ever
+    // asked to be empty.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize
     {
         match self.the_type
@@ -118,8 +231,8 @@ impl Statement
         {
             mode += match arg
             {
-                Argument::Immediate(_) | Argument::Label(_) => base * 1,
-                Argument::Position(_) | Argument::ImmediatePosition(_) => base * 0,
+                Argument::Immediate(_) | Argument::Label(_) => base,
+                Argument::Position(_) | Argument::ImmediatePosition(_) => 0,
             };
             base *= 10;
         }
@@ -198,7 +311,7 @@ impl Statement
             },
             "halt" =>
             {
-                if self.arguments.len() != 0
+                if !self.arguments.is_empty()
                 {
                     return Err(String::from("Halt doesn't take arguments"));
                 }
@@ -232,11 +345,11 @@ impl fmt::Debug for Statement
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
     {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 
-fn parseLine(line: &str, address: usize) -> Result<Statement, String>
+fn parseLine(line: &str, address: usize, number: usize) -> Result<Statement, String>
 {
     let chars: Vec<char> = line.chars().chain(vec![';']).collect();
 
@@ -246,9 +359,10 @@ fn parseLine(line: &str, address: usize) -> Result<Statement, String>
     let mut statement = Statement
     {
         the_type: StatementType::Code,
-        address: address,
+        address,
         arguments: vec![],
         head: String::new(),
+        line: number,
     };
 
     while cursor < chars.len()
@@ -347,21 +461,30 @@ pub fn parse(source: &str) -> Result<Vec<Statement>, String>
     let mut result: Vec<Statement> = vec![];
     let mut address: usize = 0;
 
-    for line in source.lines()
+    for (index, line) in source.lines().enumerate()
     {
-        let statement: Statement = parseLine(line, address)?;
+        let statement: Statement = parseLine(line, address, index + 1)?;
         address += statement.len();
         result.push(statement);
     }
     Ok(result)
 }
 
+#[allow(dead_code)]
 pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueType>, String>
+{
+    assembleWithSymbols(statements_raw).map(|(code, _)| code)
+}
+
+// Like `assemble()', but also return where the labels and variables
+// ended up.
+pub fn assembleWithSymbols(statements_raw: &Vec<Statement>)
+    -> Result<(Vec<intcode::ValueType>, SymbolTable), String>
 {
     let mut code: Vec<intcode::ValueType> = vec![];
     if statements_raw.is_empty()
     {
-        return Ok(code);
+        return Ok((code, SymbolTable::new()));
     }
 
     let mut address_labels: HashMap<&str, usize> = HashMap::new();
@@ -392,6 +515,7 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                                 Argument::Immediate(1),
                                 Argument::ImmediatePosition(stack_ptr_addr)],
                 head: String::from("add"),
+                line: statement.line,
             };
 
             // The next command after the following will write the
@@ -407,6 +531,7 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                                     (statement.address + stack_statem.len() + 4 + 3)
                                         as intcode::ValueType)],
                 head: String::from("add"),
+                line: statement.line,
             };
 
             // Write the return address to the correct place in stack.
@@ -423,6 +548,7 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                                 // instruction.
                                 Argument::ImmediatePosition(0)],
                 head: String::from("add"),
+                line: statement.line,
             };
 
             // Now we can jump to function.
@@ -434,6 +560,7 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                 arguments: vec![Argument::Immediate(1),
                                 statement.arguments[0].clone()],
                 head: String::from("jmpt"),
+                line: statement.line,
             };
 
             statements.push(stack_statem);
@@ -454,6 +581,7 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                                 Argument::ImmediatePosition(
                                     (statement.address + 4 + 4+ 2) as intcode::ValueType)],
                 head: String::from("add"),
+                line: statement.line,
             };
 
             // Decrease stack head by 1.
@@ -465,6 +593,7 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                                 Argument::Immediate(-1),
                                 Argument::ImmediatePosition(stack_ptr_addr)],
                 head: String::from("add"),
+                line: statement.line,
             };
 
             let jmp_statem = Statement
@@ -478,13 +607,13 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
                                 // previous instruction.
                                 Argument::ImmediatePosition(0)],
                 head: String::from("jmpt"),
+                line: statement.line,
             };
 
             statements.push(return_addr_statem);
             statements.push(stack_ptr_update_statem);
             statements.push(jmp_statem);
         }
-
         else
         {
             statements.push(statement.clone());
@@ -495,69 +624,60 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
     for statement in &statements
     {
         // println!("{}", statement);
-        match statement.the_type
+        if let StatementType::Label = statement.the_type
         {
-            StatementType::Label =>
+            if address_labels.contains_key(&statement.head[..])
             {
-                if address_labels.contains_key(&statement.head[..])
-                {
-                    return Err(format!("Duplicated lable: {}", statement.head));
-                }
-                address_labels.insert(&statement.head, statement.address);
-            },
-            _ => {}
+                return Err(format!("Duplicated lable: {}", statement.head));
+            }
+            address_labels.insert(&statement.head, statement.address);
         }
     }
 
     // Second pass, fill in addresses.
     for statement in &statements
     {
-        match statement.the_type
+        if let StatementType::Code = statement.the_type
         {
-            StatementType::Code =>
+            code.push(statement.opCode()?);
+            for arg in &statement.arguments
             {
-                code.push(statement.opCode()?);
-                for arg in &statement.arguments
+                match arg
                 {
-                    match arg
+                    Argument::Immediate(x) =>
+                    {
+                        code.push(*x);
+                    },
+                    Argument::Position(var) =>
                     {
-                        Argument::Immediate(x) =>
+                        if address_vars.contains_key(&var[..])
                         {
-                            code.push(x.clone());
-                        },
-                        Argument::Position(var) =>
+                            code.push(*address_vars.get(&var[..]).unwrap() as intcode::ValueType);
+                        }
+                        else
                         {
-                            if address_vars.contains_key(&var[..])
-                            {
-                                code.push(address_vars.get(&var[..]).unwrap().clone() as intcode::ValueType);
-                            }
-                            else
-                            {
-                                let addr = address_vars.len() + data_start;
-                                address_vars.insert(&var[..], addr);
-                                code.push(addr as intcode::ValueType);
-                            }
-                        },
-                        Argument::Label(label) =>
+                            let addr = address_vars.len() + data_start;
+                            address_vars.insert(&var[..], addr);
+                            code.push(addr as intcode::ValueType);
+                        }
+                    },
+                    Argument::Label(label) =>
+                    {
+                        if address_labels.contains_key(&label[..])
                         {
-                            if address_labels.contains_key(&label[..])
-                            {
-                                code.push(address_labels.get(&label[..])
-                                          .unwrap().clone() as intcode::ValueType);
-                            }
-                            else
-                            {
-                                return Err(format!("Undefined label: {}", label));
-                            }
-                        },
-                        Argument::ImmediatePosition(x) =>
+                            code.push(*address_labels.get(&label[..]).unwrap() as intcode::ValueType);
+                        }
+                        else
                         {
-                            code.push(x.clone());
-                        },
-                    }
+                            return Err(format!("Undefined label: {}", label));
+                        }
+                    },
+                    Argument::ImmediatePosition(x) =>
+                    {
+                        code.push(*x);
+                    },
                 }
-            },
-            _ => {},
+            }
         }
     }
 
@@ -567,6 +687,14 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
         code.push((code_size + 1) as intcode::ValueType);
     }
 
+    let symbols = SymbolTable
+    {
+        labels: address_labels.iter().map(|(name, addr)| (String::from(*name), *addr))
+            .collect(),
+        variables: address_vars.iter().map(|(name, addr)| (String::from(*name), *addr))
+            .collect(),
+    };
+
     // We don’t need to pre-allocate the stack and variables anymore,
     // because now the whole RAM is pre-allocated.
 
@@ -581,5 +709,49 @@ pub fn assemble(statements_raw: &Vec<Statement>) -> Result<Vec<intcode::ValueTyp
     // {
     //     code.push(0);
     // }
-    Ok(code)
+    Ok((code, symbols))
+}
+
+// Where the code of each source line starts, as (address, line)
+// pairs with lines counted from 1, for debug info.
+#[allow(dead_code)]
+pub fn lineTable(statements: &[Statement]) -> Vec<(usize, usize)>
+{
+    statements.iter().filter_map(
+        |statement| match statement.the_type
+        {
+            StatementType::Code => Some((statement.address, statement.line)),
+            _ => None,
+        }).collect()
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testSymbols()
+{
+    let source = "input x
+loop:
+add x, -1, x
+jmpt x, :loop
+halt";
+    let (code, symbols) = assembleWithSymbols(&parse(source).unwrap()).unwrap();
+    // Variables go after the code and the stack.
+    assert_eq!(code, vec![3,21,1001,21,-1,21,1005,21,2,99]);
+    assert_eq!(symbols.labels.get("loop"), Some(&2));
+    assert_eq!(symbols.variables.get("x"), Some(&21));
+    assert_eq!(symbols.describeCode(2), "loop");
+    assert_eq!(symbols.describeCode(6), "loop+4");
+    assert_eq!(symbols.describeCode(0), "0");
+    assert_eq!(symbols.describeData(21), "x");
+    assert_eq!(symbols.describeData(22), "[22]");
+
+    assert_eq!(lineTable(&parse(source).unwrap()), vec![(0, 1), (2, 3), (6, 4), (9, 5)]);
+    // Comments and blank lines are lines too.
+    let source = "; Count down.\n\n\ninput x\n\nloop:\nadd x, -1, x ; one less\njmpt x, :loop\nhalt\n";
+    assert_eq!(lineTable(&parse(source).unwrap()), vec![(0, 4), (2, 7), (6, 8), (9, 9)]);
+
+    let text = symbols.to_string();
+    assert_eq!(text, "label loop 2\nvar x 21\n");
+    assert_eq!(text.parse::<SymbolTable>().unwrap(), symbols);
 }
@@ -0,0 +1,312 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg};
+use std::cmp::Ordering;
+
+use super::ValueType;
+
+// A minimal arbitrary-precision signed integer, just enough for the
+// Intcode VM: add, multiply, compare and print.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt
+{
+    negative: bool,
+    // Magnitude in base 2^32, least significant digit first. Never
+    // has trailing zeros, so zero is an empty vector.
+    digits: Vec<u32>,
+}
+
+fn cmpMagnitude(lhs: &[u32], rhs: &[u32]) -> Ordering
+{
+    if lhs.len() != rhs.len()
+    {
+        return lhs.len().cmp(&rhs.len());
+    }
+
+    for i in (0..lhs.len()).rev()
+    {
+        if lhs[i] != rhs[i]
+        {
+            return lhs[i].cmp(&rhs[i]);
+        }
+    }
+    Ordering::Equal
+}
+
+fn addMagnitude(lhs: &[u32], rhs: &[u32]) -> Vec<u32>
+{
+    let mut result: Vec<u32> = Vec::with_capacity(lhs.len().max(rhs.len()) + 1);
+    let mut carry: u64 = 0;
+    for i in 0..lhs.len().max(rhs.len())
+    {
+        let sum = carry + *lhs.get(i).unwrap_or(&0) as u64 + *rhs.get(i).unwrap_or(&0) as u64;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0
+    {
+        result.push(carry as u32);
+    }
+    result
+}
+
+// Requires |lhs| >= |rhs|.
+fn subMagnitude(lhs: &[u32], rhs: &[u32]) -> Vec<u32>
+{
+    let mut result: Vec<u32> = Vec::with_capacity(lhs.len());
+    let mut borrow: i64 = 0;
+    for (i, digit) in lhs.iter().enumerate()
+    {
+        let mut diff = *digit as i64 - *rhs.get(i).unwrap_or(&0) as i64 - borrow;
+        if diff < 0
+        {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        else
+        {
+            borrow = 0;
+        }
+        result.push(diff as u32);
+    }
+    result
+}
+
+fn mulMagnitude(lhs: &[u32], rhs: &[u32]) -> Vec<u32>
+{
+    let mut result: Vec<u32> = vec![0; lhs.len() + rhs.len()];
+    for i in 0..lhs.len()
+    {
+        let mut carry: u64 = 0;
+        for j in 0..rhs.len()
+        {
+            let cell = result[i + j] as u64 + lhs[i] as u64 * rhs[j] as u64 + carry;
+            result[i + j] = cell as u32;
+            carry = cell >> 32;
+        }
+        result[i + rhs.len()] = carry as u32;
+    }
+    result
+}
+
+impl BigInt
+{
+    fn normalized(negative: bool, mut digits: Vec<u32>) -> Self
+    {
+        while digits.last() == Some(&0)
+        {
+            digits.pop();
+        }
+        BigInt
+        {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    pub fn zero() -> Self
+    {
+        Self::normalized(false, vec![])
+    }
+
+    pub fn isZero(&self) -> bool
+    {
+        self.digits.is_empty()
+    }
+
+    // The value as a `ValueType', if it fits.
+    pub fn toValue(&self) -> Option<ValueType>
+    {
+        if self.digits.len() > 2
+        {
+            return None;
+        }
+
+        let magnitude = self.lowMagnitude();
+        if self.negative
+        {
+            if magnitude <= (ValueType::MAX as u64) + 1
+            {
+                Some((magnitude as ValueType).wrapping_neg())
+            }
+            else
+            {
+                None
+            }
+        }
+        else if magnitude <= ValueType::MAX as u64
+        {
+            Some(magnitude as ValueType)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    // The lowest 64 bits of the value in two’s complement, i.e. what
+    // a wrapping `ValueType' would hold.
+    pub fn lowBits(&self) -> ValueType
+    {
+        let low = self.lowMagnitude() as ValueType;
+        if self.negative { low.wrapping_neg() } else { low }
+    }
+
+    fn lowMagnitude(&self) -> u64
+    {
+        let mut magnitude: u64 = 0;
+        for (i, digit) in self.digits.iter().take(2).enumerate()
+        {
+            magnitude |= (*digit as u64) << (32 * i);
+        }
+        magnitude
+    }
+}
+
+impl From<ValueType> for BigInt
+{
+    fn from(x: ValueType) -> Self
+    {
+        let magnitude = x.unsigned_abs();
+        Self::normalized(x < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Neg for &BigInt
+{
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt
+    {
+        BigInt::normalized(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt
+{
+    type Output = BigInt;
+
+    fn add(self, rhs: Self) -> BigInt
+    {
+        if self.negative == rhs.negative
+        {
+            return BigInt::normalized(self.negative, addMagnitude(&self.digits, &rhs.digits));
+        }
+
+        match cmpMagnitude(&self.digits, &rhs.digits)
+        {
+            Ordering::Less =>
+                BigInt::normalized(rhs.negative, subMagnitude(&rhs.digits, &self.digits)),
+            _ => BigInt::normalized(self.negative, subMagnitude(&self.digits, &rhs.digits)),
+        }
+    }
+}
+
+impl Mul for &BigInt
+{
+    type Output = BigInt;
+
+    fn mul(self, rhs: Self) -> BigInt
+    {
+        BigInt::normalized(self.negative != rhs.negative,
+                           mulMagnitude(&self.digits, &rhs.digits))
+    }
+}
+
+impl Ord for BigInt
+{
+    fn cmp(&self, rhs: &Self) -> Ordering
+    {
+        match (self.negative, rhs.negative)
+        {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmpMagnitude(&self.digits, &rhs.digits),
+            (true, true) => cmpMagnitude(&rhs.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt
+{
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl fmt::Display for BigInt
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        if self.isZero()
+        {
+            return write!(f, "0");
+        }
+
+        // Repeatedly divide by 10^9, collecting the remainders.
+        const CHUNK: u64 = 1_000_000_000;
+        let mut magnitude = self.digits.clone();
+        let mut chunks: Vec<u32> = vec![];
+        while !magnitude.is_empty()
+        {
+            let mut remainder: u64 = 0;
+            for digit in magnitude.iter_mut().rev()
+            {
+                let current = (remainder << 32) | (*digit as u64);
+                *digit = (current / CHUNK) as u32;
+                remainder = current % CHUNK;
+            }
+            chunks.push(remainder as u32);
+            while magnitude.last() == Some(&0)
+            {
+                magnitude.pop();
+            }
+        }
+
+        if self.negative
+        {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.last().unwrap())?;
+        for chunk in chunks.iter().rev().skip(1)
+        {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testConvert()
+{
+    for x in &[0, 1, -1, 4294967296, ValueType::MAX, ValueType::MIN]
+    {
+        let big = BigInt::from(*x);
+        assert_eq!(big.toValue(), Some(*x));
+        assert_eq!(big.to_string(), x.to_string());
+    }
+}
+
+#[test]
+fn testArithmetic()
+{
+    let max = BigInt::from(ValueType::MAX);
+    let one = BigInt::from(1);
+    let sum = &max + &one;
+    assert_eq!(sum.toValue(), None);
+    assert_eq!(sum.to_string(), "9223372036854775808");
+    assert_eq!(sum.lowBits(), ValueType::MIN);
+    assert_eq!((&sum + &(-&one)).toValue(), Some(ValueType::MAX));
+
+    let product = &max * &max;
+    assert_eq!(product.to_string(), "85070591730234615847396907784232501249");
+    assert_eq!((&product * &BigInt::from(-1)).to_string(),
+               "-85070591730234615847396907784232501249");
+    assert!(BigInt::from(-5) < BigInt::from(3));
+    assert!(-&product < BigInt::from(ValueType::MIN));
+    assert!((&BigInt::from(7) + &BigInt::from(-7)).isZero());
+}
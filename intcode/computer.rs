@@ -0,0 +1,1492 @@
+#![allow(non_snake_case)]
+
+use std::vec::Vec;
+use std::collections::HashMap;
+
+use super::bigint::BigInt;
+
+pub type ValueType = i64;
+
+#[allow(dead_code)]
+pub fn parse(code: &str) -> Vec<ValueType>
+{
+    code.split(',').map(
+        |part|
+        {
+            let op = part.trim().parse::<ValueType>();
+            if op.is_err()
+            {
+                panic!("Cannot convert '{}'.", part);
+            }
+            op.unwrap()
+        }).collect()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArgMode
+{
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl ArgMode
+{
+    pub fn fromDigit(x: u8) -> Result<ArgMode, String>
+    {
+        match x
+        {
+            0 => Ok(ArgMode::Position),
+            1 => Ok(ArgMode::Immediate),
+            2 => Ok(ArgMode::Relative),
+            _ => Err(format!("Invalid arg mode {}", x)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OpCode
+{
+    pub arg_count: u8,
+    pub code: u8,
+    pub arg_modes: [ArgMode; 3],
+}
+
+impl OpCode
+{
+    pub fn fromInt(code: ValueType) -> Result<OpCode, String>
+    {
+        if code < 0
+        {
+            return Err(format!("Invalid opcode {}", code));
+        }
+
+        let code2: u8 =  (code % 100) as u8;
+        let mut code_modes = code / 100;
+        let arg_count: u8 = match code2
+        {
+            1 | 2 => 3,
+            3 | 4 => 1,
+            5 | 6 => 2,
+            7 | 8 => 3,
+            9 => 1,
+            99 => 0,
+            _ => { return Err(format!("Invalid opcode {}", code)); },
+        };
+
+        let mut modes: [ArgMode; 3] = [ArgMode::Position,
+                                       ArgMode::Position,
+                                       ArgMode::Position];
+
+        for mode in modes.iter_mut()
+        {
+            *mode = ArgMode::fromDigit((code_modes % 10) as u8)?;
+            code_modes /= 10;
+        };
+
+        Ok(OpCode
+           {
+               arg_count,
+               code: code2,
+               arg_modes: modes,
+           })
+    }
+
+    // The name of the instruction, as used by the assembler.
+    #[allow(dead_code)]
+    pub fn name(&self) -> &'static str
+    {
+        match self.code
+        {
+            1 => "add",
+            2 => "mult",
+            3 => "input",
+            4 => "output",
+            5 => "jmpt",
+            6 => "jmpf",
+            7 => "less",
+            8 => "eq",
+            9 => "offset",
+            99 => "halt",
+            _ => unreachable!(),
+        }
+    }
+}
+
+// How the computer deals with results of add and mult that do not fit
+// in a `ValueType'.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Arithmetic
+{
+    // Wrap around in two’s complement, in debug and release builds
+    // alike.
+    Wrapping,
+    // Stop the computer with an error. This is the default.
+    Checked,
+    // Keep the full result. Such values can be computed with,
+    // compared and output, but not used as addresses or jump targets.
+    Big,
+}
+
+// Where a computer stopped, when run with `run()' or `runFor()'.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum State
+{
+    // Still going, but used up the steps it was given.
+    Running,
+    // Hit an input instruction with no queued input.
+    WaitingInput,
+    Halted,
+}
+
+// A write into a memory cell that has already been executed as code,
+// either as an opcode or as one of its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelfModification
+{
+    // Address of the instruction that did the write.
+    pub writer: usize,
+    pub address: usize,
+    pub old: ValueType,
+    pub new: ValueType,
+}
+
+// Hooks into the execution of an `IntCodeComputer'. All methods have
+// empty default implementations, so a tracer only needs to implement
+// what it cares about.
+pub trait Tracer
+{
+    // Called right before the instruction at `computer.cursor()' is
+    // executed.
+    fn step(&mut self, _computer: &IntCodeComputer) {}
+
+    // Called on every self-modification, if tracking is turned on
+    // with `trackSelfModification()'.
+    fn selfModification(&mut self, _event: &SelfModification) {}
+}
+
+// The state of a computer at some point, to go back to later with
+// `restore()'. The tracer and self-modification tracking are not part
+// of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot
+{
+    mem: Vec<ValueType>,
+    cursor: usize,
+    halt: bool,
+    input: Vec<ValueType>,
+    cursor_input: usize,
+    output: Vec<ValueType>,
+    offset: ValueType,
+    one_input: ValueType,
+    one_output: ValueType,
+    arithmetic: Arithmetic,
+    big_cells: HashMap<usize, BigInt>,
+    big_output: HashMap<usize, BigInt>,
+    one_big_output: Option<BigInt>,
+    error: Option<String>,
+    fault: bool,
+}
+
+pub struct IntCodeComputer
+{
+    pub mem: Vec<ValueType>,
+    cursor: usize,
+    halt: bool,
+    input: Vec<ValueType>,
+    cursor_input: usize,
+    pub output: Vec<ValueType>,
+    offset: ValueType,
+
+    one_input: ValueType,
+    one_output: ValueType,
+
+    track_self_modification: bool,
+    // Which cells have been executed as code. Only maintained when
+    // `track_self_modification' is on.
+    executed: Vec<bool>,
+    self_modifications: Vec<SelfModification>,
+    tracer: Option<Box<dyn Tracer + Send>>,
+
+    arithmetic: Arithmetic,
+    // Cells holding values that do not fit in a `ValueType', in `Big'
+    // arithmetic mode. The corresponding cells in `mem' hold the
+    // lowest bits of those values.
+    big_cells: HashMap<usize, BigInt>,
+    // Outputs that do not fit in a `ValueType', by index into
+    // `output'.
+    big_output: HashMap<usize, BigInt>,
+    one_big_output: Option<BigInt>,
+    error: Option<String>,
+    // Whether the error is an access outside of memory.
+    fault: bool,
+}
+
+impl IntCodeComputer
+{
+    #[allow(dead_code)]
+    pub fn new() -> IntCodeComputer
+    {
+        IntCodeComputer
+        {
+            mem: vec![0; 8192],
+            cursor: 0,
+            halt: false,
+            input: vec![],
+            cursor_input: 0,
+            output: vec![],
+            offset: 0,
+
+            one_input: 0,
+            one_output: 0,
+
+            track_self_modification: false,
+            executed: vec![],
+            self_modifications: vec![],
+            tracer: None,
+
+            arithmetic: Arithmetic::Checked,
+            big_cells: HashMap::new(),
+            big_output: HashMap::new(),
+            one_big_output: None,
+            error: None,
+            fault: false,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn reset(&mut self)
+    {
+        self.mem = vec![0; 8192];
+        self.cursor = 0;
+        self.halt = false;
+        self.input.clear();
+        self.cursor_input = 0;
+        self.output.clear();
+        self.offset = 0;
+
+        self.one_input = 0;
+        self.one_output = 0;
+
+        self.executed.clear();
+        self.self_modifications.clear();
+
+        self.big_cells.clear();
+        self.big_output.clear();
+        self.one_big_output = None;
+        self.error = None;
+        self.fault = false;
+    }
+
+    #[allow(dead_code)]
+    pub fn snapshot(&self) -> Snapshot
+    {
+        Snapshot
+        {
+            mem: self.mem.clone(),
+            cursor: self.cursor,
+            halt: self.halt,
+            input: self.input.clone(),
+            cursor_input: self.cursor_input,
+            output: self.output.clone(),
+            offset: self.offset,
+            one_input: self.one_input,
+            one_output: self.one_output,
+            arithmetic: self.arithmetic,
+            big_cells: self.big_cells.clone(),
+            big_output: self.big_output.clone(),
+            one_big_output: self.one_big_output.clone(),
+            error: self.error.clone(),
+            fault: self.fault,
+        }
+    }
+
+    // Go back to the state in `snapshot'. The tracer stays.
+    #[allow(dead_code)]
+    pub fn restore(&mut self, snapshot: &Snapshot)
+    {
+        let snapshot = snapshot.clone();
+        self.mem = snapshot.mem;
+        self.cursor = snapshot.cursor;
+        self.halt = snapshot.halt;
+        self.input = snapshot.input;
+        self.cursor_input = snapshot.cursor_input;
+        self.output = snapshot.output;
+        self.offset = snapshot.offset;
+        self.one_input = snapshot.one_input;
+        self.one_output = snapshot.one_output;
+        self.arithmetic = snapshot.arithmetic;
+        self.big_cells = snapshot.big_cells;
+        self.big_output = snapshot.big_output;
+        self.one_big_output = snapshot.one_big_output;
+        self.error = snapshot.error;
+        self.fault = snapshot.fault;
+    }
+
+    #[allow(dead_code)]
+    pub fn halted(&self) -> bool
+    {
+        self.halt
+    }
+
+    #[allow(dead_code)]
+    pub fn setArithmetic(&mut self, arithmetic: Arithmetic)
+    {
+        self.arithmetic = arithmetic;
+    }
+
+    #[allow(dead_code)]
+    pub fn arithmetic(&self) -> Arithmetic
+    {
+        self.arithmetic
+    }
+
+    // The error that stopped the computer, if any. A computer with an
+    // error is also halted, with the cursor at the failed instruction.
+    #[allow(dead_code)]
+    pub fn error(&self) -> Option<&String>
+    {
+        self.error.as_ref()
+    }
+
+    // Whether the computer stopped because it accessed memory outside
+    // of what it has. The error says where.
+    #[allow(dead_code)]
+    pub fn faulted(&self) -> bool
+    {
+        self.fault
+    }
+
+    // The value in memory at `address' in full precision.
+    #[allow(dead_code)]
+    pub fn bigValue(&self, address: usize) -> BigInt
+    {
+        match self.big_cells.get(&address)
+        {
+            Some(x) => x.clone(),
+            None => BigInt::from(self.mem[address]),
+        }
+    }
+
+    // All outputs so far in full precision.
+    #[allow(dead_code)]
+    pub fn bigOutput(&self) -> Vec<BigInt>
+    {
+        self.output.iter().enumerate().map(
+            |(i, x)| match self.big_output.get(&i)
+            {
+                Some(big) => big.clone(),
+                None => BigInt::from(*x),
+            }).collect()
+    }
+
+    // The full-precision value of the output last returned by
+    // `pipe()', if it does not fit in a `ValueType'.
+    #[allow(dead_code)]
+    pub fn lastBigOutput(&self) -> Option<&BigInt>
+    {
+        self.one_big_output.as_ref()
+    }
+
+    // ========== Inspection ========================================>
+
+    // Address of the next instruction to execute.
+    #[allow(dead_code)]
+    pub fn cursor(&self) -> usize
+    {
+        self.cursor
+    }
+
+    #[allow(dead_code)]
+    pub fn relativeBase(&self) -> ValueType
+    {
+        self.offset
+    }
+
+    // Turn on or off the detection of self-modifying code. When on,
+    // the computer remembers every cell it executes, and records a
+    // `SelfModification' whenever one of those cells is written
+    // afterwards. Turning it off discards what has been recorded.
+    #[allow(dead_code)]
+    pub fn trackSelfModification(&mut self, on: bool)
+    {
+        self.track_self_modification = on;
+        self.executed.clear();
+        self.self_modifications.clear();
+    }
+
+    // All self-modifications detected so far, in the order they
+    // happened.
+    #[allow(dead_code)]
+    pub fn selfModifications(&self) -> &Vec<SelfModification>
+    {
+        &self.self_modifications
+    }
+
+    // Whether the cell at `address' has been executed as code. Always
+    // false if self-modification tracking is off.
+    #[allow(dead_code)]
+    pub fn executed(&self, address: usize) -> bool
+    {
+        address < self.executed.len() && self.executed[address]
+    }
+
+    #[allow(dead_code)]
+    pub fn setTracer(&mut self, tracer: Option<Box<dyn Tracer + Send>>)
+    {
+        self.tracer = tracer;
+    }
+
+    #[allow(dead_code)]
+    pub fn takeTracer(&mut self) -> Option<Box<dyn Tracer + Send>>
+    {
+        self.tracer.take()
+    }
+
+    pub fn loadCode(&mut self, code: &[ValueType])
+    {
+        self.mem[..code.len()].copy_from_slice(code);
+    }
+
+    // Load a program from `bytes', which can be an image or Intcode
+    // text, and start at its entry point.
+    pub fn loadImage(&mut self, bytes: &[u8]) -> Result<(), String>
+    {
+        let image = super::image::Image::load(bytes)?;
+        if image.code.len() > self.mem.len()
+        {
+            return Err(format!("Program of {} cells does not fit in memory", image.code.len()));
+        }
+        image.loadInto(self);
+        Ok(())
+    }
+
+    // Start running from `address' instead of 0, for programs with an
+    // entry point.
+    #[allow(dead_code)]
+    pub fn setCursor(&mut self, address: usize)
+    {
+        self.cursor = address;
+    }
+
+    // Decode the instruction at the cursor. An invalid one stops the
+    // computer with an error.
+    fn getNextOpCode(&mut self) -> Option<OpCode>
+    {
+        if self.halt
+        {
+            return None;
+        }
+
+        if self.cursor >= self.mem.len()
+        {
+            self.failAccess(format!("Cursor {} is out of memory", self.cursor));
+            None
+        }
+        else if !self.checkSmall(self.cursor)
+        {
+            None
+        }
+        else if let Ok(code) = OpCode::fromInt(self.mem[self.cursor])
+        {
+            Some(code)
+        }
+        else
+        {
+            self.fail(format!("Invalid opcode {}", self.mem[self.cursor]));
+            None
+        }
+    }
+
+    // Execute `code'. Return false without doing anything if one of
+    // its arguments is out of memory.
+    fn step(&mut self, code: &OpCode) -> bool
+    {
+        // The target of a jump is only read when the jump is taken,
+        // see `jumpTo()'.
+        let is_jump = code.code == 5 || code.code == 6;
+        for index in 0..code.arg_count
+        {
+            if is_jump && index == 1
+            {
+                continue;
+            }
+            if !self.checkArg(code, index)
+            {
+                return false;
+            }
+        }
+
+        if let Some(mut tracer) = self.tracer.take()
+        {
+            tracer.step(self);
+            self.tracer = Some(tracer);
+        }
+
+        if self.track_self_modification
+        {
+            let end = self.cursor + (code.arg_count as usize) + 1;
+            if self.executed.len() < end
+            {
+                self.executed.resize(self.mem.len().max(end), false);
+            }
+            for cell in &mut self.executed[self.cursor..end]
+            {
+                *cell = true;
+            }
+        }
+
+        match code.code
+        {
+            99 => { self.evalHalt(code); },
+            1 => { self.evalAdd(code); },
+            2 => { self.evalMult(code); },
+            3 => { self.evalInput(code); },
+            4 => { self.evalOutput(code); },
+            5 => { self.evalJmpTrue(code); },
+            6 => { self.evalJmpFalse(code); },
+            7 => { self.evalLess(code); },
+            8 => { self.evalEqual(code); },
+            9 => { self.evalOffset(code); }
+            _ => unreachable!(),
+        };
+        true
+    }
+
+    // Execute one instruction, taking input from `self.input' and
+    // appending to `self.output'.
+    fn runOne(&mut self) -> State
+    {
+        if self.halt
+        {
+            return State::Halted;
+        }
+
+        let code: OpCode = match self.getNextOpCode()
+        {
+            Some(code) => code,
+            None => { return State::Halted; },
+        };
+
+        if code.code == 3    // input
+        {
+            // Nowhere to put the input is a fault even without one.
+            if !self.checkArg(&code, 0)
+            {
+                return State::Halted;
+            }
+            if self.cursor_input >= self.input.len()
+            {
+                return State::WaitingInput;
+            }
+            self.one_input = self.input[self.cursor_input];
+        }
+
+        if !self.step(&code)
+        {
+            return State::Halted;
+        }
+        if code.code == 3
+        {
+            self.cursor_input += 1;
+        }
+
+        if code.code == 4    // Output
+        {
+            if let Some(big) = self.one_big_output.take()
+            {
+                self.big_output.insert(self.output.len(), big);
+            }
+            self.output.push(self.one_output);
+        }
+
+        if self.halt
+        {
+            State::Halted
+        }
+        else
+        {
+            State::Running
+        }
+    }
+
+    // Run to the end, with `input' if given. Panics when the computer
+    // stops with an error or runs out of input. Use `run()' to deal
+    // with those.
+    #[allow(dead_code)]
+    pub fn eval(&mut self, input: Option<&Vec<ValueType>>)
+    {
+        if let Some(input) = input
+        {
+            self.input = input.clone();
+        }
+
+        loop
+        {
+            match self.runOne()
+            {
+                State::Running => {},
+                State::Halted => { break; },
+                State::WaitingInput => { panic!("Ran out of input at {}", self.cursor); },
+            }
+        }
+        if let Some(error) = &self.error
+        {
+            panic!("{} at {}", error, self.cursor);
+        }
+    }
+
+    // Queue an input for `run()' and `runFor()'. Inputs already taken
+    // are dropped, so that a long run does not keep them all.
+    #[allow(dead_code)]
+    pub fn pushInput(&mut self, input: ValueType)
+    {
+        if self.cursor_input == self.input.len()
+        {
+            self.input.clear();
+            self.cursor_input = 0;
+        }
+        self.input.push(input);
+    }
+
+    // Whether there are queued inputs that have not been consumed.
+    #[allow(dead_code)]
+    pub fn hasPendingInput(&self) -> bool
+    {
+        self.cursor_input < self.input.len()
+    }
+
+    // Remove and return all outputs so far.
+    #[allow(dead_code)]
+    pub fn takeOutput(&mut self) -> Vec<ValueType>
+    {
+        self.big_output.clear();
+        std::mem::take(&mut self.output)
+    }
+
+    // Run until the computer halts, or needs an input that has not
+    // been queued with `pushInput()'. Never returns
+    // `State::Running'.
+    #[allow(dead_code)]
+    pub fn run(&mut self) -> State
+    {
+        loop
+        {
+            let state = self.runOne();
+            if state != State::Running
+            {
+                return state;
+            }
+        }
+    }
+
+    // Like `run()', but execute at most `steps' instructions.
+    #[allow(dead_code)]
+    pub fn runFor(&mut self, steps: usize) -> State
+    {
+        for _ in 0..steps
+        {
+            let state = self.runOne();
+            if state != State::Running
+            {
+                return state;
+            }
+        }
+        if self.halt { State::Halted } else { State::Running }
+    }
+
+    // As soon as the computer hits the next input, do the input and
+    // pause there. Doesn’t write to `self.input’. Also return when
+    // halted.
+    #[allow(dead_code)]
+    pub fn consumeSingleInput(&mut self, input: ValueType)
+    {
+        self.one_input = input;
+        loop
+        {
+            let code: OpCode = match self.getNextOpCode()
+            {
+                Some(code) => code,
+                None => { return; },
+            };
+            if !self.step(&code)
+            {
+                return;
+            }
+
+            if code.code == 3    // Input
+            {
+                return;
+            }
+
+            if self.halt
+            {
+                return;
+            }
+        }
+    }
+
+    // Work in pipe mode. Ever time this function calls, it takes 1
+    // input, runs the computer until there’s an output, and returns
+    // that output. The computer then pause at the current state. This
+    // function can be then called to process further inputs. If the
+    // computer halts in the process, returns None.
+    //
+    // The code is directly taken from memory.
+    #[allow(dead_code)]
+    pub fn pipe(&mut self, input: Option<ValueType>) -> Option<ValueType>
+    {
+        if let Some(x) = input
+        {
+            self.one_input = x;
+        }
+
+        loop
+        {
+            let code: OpCode = match self.getNextOpCode()
+            {
+                Some(code) => code,
+                None => { return None; },
+            };
+
+            if !self.step(&code)
+            {
+                return None;
+            }
+            if code.code == 4    // Output
+            {
+                return Some(self.one_output);
+            }
+
+            if self.halt
+            {
+                return None;
+            }
+        }
+    }
+
+    // The address of argument `index' of `code', or why it is not in
+    // memory.
+    fn checkedAddress(&self, code: &OpCode, index: u8) -> Result<usize, String>
+    {
+        let param = self.cursor + (index as usize) + 1;
+        if param >= self.mem.len()
+        {
+            return Err(format!("Instruction runs past the end of memory at {}", param));
+        }
+        let address = match code.arg_modes[index as usize]
+        {
+            ArgMode::Position => self.mem[param],
+            ArgMode::Immediate => { return Ok(param); },
+            ArgMode::Relative => match self.mem[param].checked_add(self.offset)
+            {
+                Some(address) => address,
+                None => { return Err(String::from("Overflow in relative address")); },
+            },
+        };
+        if address < 0 || address as usize >= self.mem.len()
+        {
+            return Err(format!("Address {} is out of memory", address));
+        }
+        Ok(address as usize)
+    }
+
+    // Check that argument `index' of `code' is in memory, and not found
+    // through a big value. Stop the computer if not.
+    fn checkArg(&mut self, code: &OpCode, index: u8) -> bool
+    {
+        let param = self.cursor + (index as usize) + 1;
+        if code.arg_modes[index as usize] != ArgMode::Immediate && !self.checkSmall(param)
+        {
+            return false;
+        }
+        match self.checkedAddress(code, index)
+        {
+            Ok(_) => true,
+            Err(error) =>
+            {
+                self.failAccess(error);
+                false
+            },
+        }
+    }
+
+    // Stop the computer if the cell at `address' holds a big value,
+    // which cannot be used as an opcode or an address.
+    fn checkSmall(&mut self, address: usize) -> bool
+    {
+        if let Some(value) = self.big_cells.get(&address)
+        {
+            let error = format!("Value {} is too large to be used here", value);
+            self.fail(error);
+            return false;
+        }
+        true
+    }
+
+    // Only called on instructions that `step()' has checked.
+    fn getAddress(&self, code: &OpCode, index: u8) -> usize
+    {
+        self.checkedAddress(code, index).unwrap()
+    }
+
+    fn getArg(&self, code: &OpCode, index: u8) -> ValueType
+    {
+        self.mem[self.getAddress(code, index)]
+    }
+
+    fn getBigArg(&self, code: &OpCode, index: u8) -> BigInt
+    {
+        self.bigValue(self.getAddress(code, index))
+    }
+
+    fn isBigArg(&self, code: &OpCode, index: u8) -> bool
+    {
+        !self.big_cells.is_empty() && self.big_cells.contains_key(&self.getAddress(code, index))
+    }
+
+    // Get an argument that is used as an address or a jump target.
+    // Stop the computer and return None if it does not fit in a
+    // `ValueType'.
+    fn getSmallArg(&mut self, code: &OpCode, index: u8) -> Option<ValueType>
+    {
+        if self.isBigArg(code, index)
+        {
+            let value = self.getBigArg(code, index);
+            self.fail(format!("Value {} is too large to be used here", value));
+            None
+        }
+        else
+        {
+            Some(self.getArg(code, index))
+        }
+    }
+
+    // A jump past the end of memory is caught when the next
+    // instruction is decoded.
+    fn jump(&mut self, target: ValueType)
+    {
+        if target < 0
+        {
+            self.failAccess(format!("Jump to {} is out of memory", target));
+        }
+        else
+        {
+            self.cursor = target as usize;
+        }
+    }
+
+    // Stop the computer because of an error.
+    fn fail(&mut self, error: String)
+    {
+        self.error = Some(error);
+        self.halt = true;
+    }
+
+    // Stop the computer because it accessed memory it does not have.
+    fn failAccess(&mut self, error: String)
+    {
+        self.fail(error);
+        self.fault = true;
+    }
+
+    // All memory writes done by instructions go through here, so that
+    // self-modifications can be detected.
+    fn write(&mut self, address: usize, value: ValueType)
+    {
+        if self.track_self_modification && self.executed(address)
+        {
+            let event = SelfModification
+            {
+                writer: self.cursor,
+                address,
+                old: self.mem[address],
+                new: value,
+            };
+
+            if let Some(tracer) = &mut self.tracer
+            {
+                tracer.selfModification(&event);
+            }
+            self.self_modifications.push(event);
+        }
+        if !self.big_cells.is_empty()
+        {
+            self.big_cells.remove(&address);
+        }
+        self.mem[address] = value;
+    }
+
+    fn writeBig(&mut self, address: usize, value: BigInt)
+    {
+        match value.toValue()
+        {
+            Some(x) => self.write(address, x),
+            None =>
+            {
+                self.write(address, value.lowBits());
+                self.big_cells.insert(address, value);
+            },
+        }
+    }
+
+    // Shared implementation of add and mult.
+    fn evalArithmetic(&mut self, code: &OpCode, name: &str,
+                      checked: fn(ValueType, ValueType) -> Option<ValueType>,
+                      wrapping: fn(ValueType, ValueType) -> ValueType,
+                      big: fn(&BigInt, &BigInt) -> BigInt)
+    {
+        let result_addr = self.getAddress(code, 2);
+        match self.arithmetic
+        {
+            Arithmetic::Wrapping =>
+            {
+                let result = wrapping(self.getArg(code, 0), self.getArg(code, 1));
+                self.write(result_addr, result);
+            },
+            Arithmetic::Checked =>
+            {
+                let lhs = self.getArg(code, 0);
+                let rhs = self.getArg(code, 1);
+                if let Some(result) = checked(lhs, rhs)
+                {
+                    self.write(result_addr, result);
+                }
+                else
+                {
+                    self.fail(format!("Overflow in {} of {} and {}", name, lhs, rhs));
+                    return;
+                }
+            },
+            Arithmetic::Big =>
+            {
+                let result = big(&self.getBigArg(code, 0), &self.getBigArg(code, 1));
+                self.writeBig(result_addr, result);
+            },
+        }
+        self.skip(code);
+    }
+
+    fn skip(&mut self, code: &OpCode)
+    {
+        self.cursor += (code.arg_count as usize) + 1;
+    }
+
+    fn evalHalt(&mut self, _: &OpCode)
+    {
+        self.halt = true;
+    }
+
+    fn evalAdd(&mut self, code: &OpCode)
+    {
+        self.evalArithmetic(code, "add", ValueType::checked_add,
+                            ValueType::wrapping_add, |lhs, rhs| lhs + rhs);
+    }
+
+    fn evalMult(&mut self, code: &OpCode)
+    {
+        self.evalArithmetic(code, "mult", ValueType::checked_mul,
+                            ValueType::wrapping_mul, |lhs, rhs| lhs * rhs);
+    }
+
+    fn evalInput(&mut self, code: &OpCode)
+    {
+        let result_addr = self.getAddress(code, 0);
+        self.write(result_addr, self.one_input);
+        self.skip(code);
+    }
+
+    fn evalOutput(&mut self, code: &OpCode)
+    {
+        self.one_output = self.getArg(code, 0);
+        self.one_big_output = if self.isBigArg(code, 0)
+        {
+            Some(self.getBigArg(code, 0))
+        }
+        else
+        {
+            None
+        };
+        self.skip(code);
+    }
+
+    // A big value is never zero.
+    fn isTrue(&self, code: &OpCode, index: u8) -> bool
+    {
+        self.getArg(code, index) != 0 || self.isBigArg(code, index)
+    }
+
+    fn jumpTo(&mut self, code: &OpCode)
+    {
+        if !self.checkArg(code, 1)
+        {
+            return;
+        }
+        if let Some(target) = self.getSmallArg(code, 1)
+        {
+            self.jump(target);
+        }
+    }
+
+    fn evalJmpTrue(&mut self, code: &OpCode)
+    {
+        if self.isTrue(code, 0)
+        {
+            self.jumpTo(code);
+        }
+        else
+        {
+            self.skip(code);
+        }
+    }
+
+    fn evalJmpFalse(&mut self, code: &OpCode)
+    {
+        if !self.isTrue(code, 0)
+        {
+            self.jumpTo(code);
+        }
+        else
+        {
+            self.skip(code);
+        }
+    }
+
+    fn evalLess(&mut self, code: &OpCode)
+    {
+        let less = if self.isBigArg(code, 0) || self.isBigArg(code, 1)
+        {
+            self.getBigArg(code, 0) < self.getBigArg(code, 1)
+        }
+        else
+        {
+            self.getArg(code, 0) < self.getArg(code, 1)
+        };
+        let result_addr = self.getAddress(code, 2);
+        self.write(result_addr, if less {1} else {0});
+        self.skip(code);
+    }
+
+    fn evalEqual(&mut self, code: &OpCode)
+    {
+        let equal = if self.isBigArg(code, 0) || self.isBigArg(code, 1)
+        {
+            self.getBigArg(code, 0) == self.getBigArg(code, 1)
+        }
+        else
+        {
+            self.getArg(code, 0) == self.getArg(code, 1)
+        };
+        let result_addr = self.getAddress(code, 2);
+        self.write(result_addr, if equal {1} else {0});
+        self.skip(code);
+    }
+
+    fn evalOffset(&mut self, code: &OpCode)
+    {
+        let delta = match self.getSmallArg(code, 0)
+        {
+            Some(x) => x,
+            None => { return; },
+        };
+
+        // Addresses are never big, so neither is the relative base.
+        if self.arithmetic != Arithmetic::Wrapping
+        {
+            match self.offset.checked_add(delta)
+            {
+                Some(offset) => { self.offset = offset; },
+                None =>
+                {
+                    self.fail(String::from("Overflow in relative base"));
+                    return;
+                },
+            }
+        }
+        else
+        {
+            self.offset = self.offset.wrapping_add(delta);
+        }
+        self.skip(code);
+    }
+}
+
+impl Default for IntCodeComputer
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testAdd()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![1,0,0,0,99]);
+    computer.eval(None);
+    assert_eq!(computer.mem[..5].to_vec(), vec![2,0,0,0,99]);
+}
+
+#[test]
+fn testMult()
+{
+    let mut computer = IntCodeComputer::new();
+
+    computer.loadCode(&vec![2,3,0,3,99]);
+    computer.eval(None);
+    assert_eq!(computer.mem[..5].to_vec(), vec![2,3,0,6,99]);
+
+    computer.reset();
+    computer.loadCode(&vec![2,4,4,5,99,0]);
+    computer.eval(None);
+    assert_eq!(computer.mem[..6].to_vec(), vec![2,4,4,5,99,9801]);
+}
+
+#[test]
+fn testBasic()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![1,1,1,4,99,5,6,0,99]);
+    computer.eval(None);
+    let expected = vec![30,1,1,4,2,5,6,0,99];
+    assert_eq!(computer.mem[..expected.len()].to_vec(), expected);
+}
+
+#[test]
+fn testIO()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,0,99]);
+    computer.eval(Some(&vec![10]));
+    assert_eq!(computer.mem[..3].to_vec(), vec![10,0,99]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,0,3,1,99]);
+    computer.eval(Some(&vec![10,20]));
+    assert_eq!(computer.mem[..5].to_vec(), vec![10,20,3,1,99]);
+
+    computer.reset();
+    computer.loadCode(&vec![4,0,99]);
+    computer.eval(None);
+    assert_eq!(computer.output, vec![4]);
+
+    computer.reset();
+    computer.loadCode(&vec![4,0,4,4,99]);
+    computer.eval(None);
+    assert_eq!(computer.output, vec![4,99]);
+}
+
+#[test]
+fn testModes()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![1002,4,3,4,33]);
+    computer.eval(None);
+    assert_eq!(computer.mem[..5].to_vec(), vec![1002,4,3,4,99]);
+
+    computer.reset();
+    computer.loadCode(&vec![1101,100,-1,4,0]);
+    computer.eval(None);
+    assert_eq!(computer.mem[..5].to_vec(), vec![1101,100,-1,4,99]);
+}
+
+#[test]
+fn testConditional()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,9,8,9,10,9,4,9,99,-1,8]);
+    computer.eval(Some(&vec![8]));
+    assert_eq!(computer.output, vec![1]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,9,7,9,10,9,4,9,99,-1,8]);
+    computer.eval(Some(&vec![7]));
+    assert_eq!(computer.output, vec![1]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,3,1108,-1,8,3,4,3,99]);
+    computer.eval(Some(&vec![7]));
+    assert_eq!(computer.output, vec![0]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,3,1107,-1,8,3,4,3,99]);
+    computer.eval(Some(&vec![8]));
+    assert_eq!(computer.output, vec![0]);
+}
+
+#[test]
+fn testJump()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9]);
+    computer.eval(Some(&vec![0]));
+    assert_eq!(computer.output, vec![0]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9]);
+    computer.eval(Some(&vec![-1]));
+    assert_eq!(computer.output, vec![1]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,3,1105,-1,9,1101,0,0,12,4,12,99,1]);
+    computer.eval(Some(&vec![0]));
+    assert_eq!(computer.output, vec![0]);
+
+    computer.reset();
+    computer.loadCode(&vec![3,3,1105,-1,9,1101,0,0,12,4,12,99,1]);
+    computer.eval(Some(&vec![1234]));
+    assert_eq!(computer.output, vec![1]);
+}
+
+#[test]
+fn testPipe()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,20,1001,20,1,21,4,21,99,10,0,0,0,0,0,0,0,0,0,0,0,0]);
+    assert_eq!(computer.pipe(Some(10)).unwrap(), 11);
+
+    computer.reset();
+    computer.loadCode(&vec![3,28,1001,28,1,29,4,29,3,28,1001,28,2,29,4,29,99,
+                           18,0,0,0,0,0,0,0,0,0,0,0,0]);
+    assert_eq!(computer.pipe(Some(10)).unwrap(), 11);
+    assert_eq!(computer.pipe(Some(11)).unwrap(), 13);
+    assert!(computer.pipe(Some(0)).is_none());
+}
+
+#[test]
+fn testOffset()
+{
+    let mut computer = IntCodeComputer::new();
+
+    let code = vec![109, 1, 1201, -1, 1, 0, 99];
+    computer.loadCode(&code);
+    computer.eval(None);
+    assert_eq!(computer.mem[0], 110 as ValueType);
+
+    computer.reset();
+    let code = vec![109, 1, 1201, -1, 1, 0, 204, -1, 99];
+    computer.loadCode(&code);
+    computer.eval(None);
+    assert_eq!(computer.output, vec![110]);
+
+    computer.reset();
+    let code = vec![109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+    computer.loadCode(&code);
+    computer.eval(None);
+    assert_eq!(computer.output, code);
+}
+
+#[test]
+fn testLong()
+{
+    let mut computer = IntCodeComputer::new();
+    let code = vec![1102,34915192,34915192,7,4,7,99,0];
+    computer.loadCode(&code);
+    computer.eval(None);
+    assert_eq!(computer.output[0].to_string().len(), 16);
+}
+
+#[test]
+fn testSelfModification()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.trackSelfModification(true);
+    computer.loadCode(&vec![1,0,0,5,99,0]);
+    computer.eval(None);
+    assert!(computer.selfModifications().is_empty());
+
+    // Overwrite the add instruction itself after running it.
+    computer.reset();
+    computer.loadCode(&vec![1101,5,5,0,99]);
+    computer.eval(None);
+    assert_eq!(computer.selfModifications(),
+               &vec![SelfModification { writer: 0, address: 0, old: 1101, new: 10 }]);
+
+    // Patch the parameter of a previously executed instruction, and
+    // jump back to it.
+    computer.reset();
+    computer.loadCode(&vec![1101,1,1,20,1001,1,1,1,1007,1,3,21,1005,21,0,99]);
+    computer.eval(None);
+    let events = computer.selfModifications();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].writer, 4);
+    assert_eq!(events[0].address, 1);
+    assert!(computer.executed(0));
+    assert!(!computer.executed(20));
+}
+
+#[test]
+fn testArithmeticModes()
+{
+    // Square 2^32, and output the result.
+    let code = vec![1102,4294967296,4294967296,7,4,7,99,0];
+    let mut computer = IntCodeComputer::new();
+    assert_eq!(computer.arithmetic(), Arithmetic::Checked);
+    computer.setArithmetic(Arithmetic::Wrapping);
+    computer.loadCode(&code);
+    computer.eval(None);
+    assert_eq!(computer.output, vec![0]);
+    assert!(computer.error().is_none());
+
+    computer.reset();
+    computer.setArithmetic(Arithmetic::Checked);
+    computer.loadCode(&code);
+    assert_eq!(computer.run(), State::Halted);
+    assert!(computer.output.is_empty());
+    assert!(computer.error().unwrap().starts_with("Overflow in mult"));
+
+    computer.reset();
+    computer.setArithmetic(Arithmetic::Big);
+    computer.loadCode(&code);
+    computer.eval(None);
+    assert!(computer.error().is_none());
+    assert_eq!(computer.bigOutput()[0].to_string(), "18446744073709551616");
+    assert_eq!(computer.bigValue(7).to_string(), "18446744073709551616");
+
+    // Compare a big value, then try to jump to it.
+    computer.reset();
+    computer.loadCode(&vec![1102,4294967296,4294967296,20,107,1,20,21,4,21,
+                            5,21,20,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.output, vec![1]);
+    assert!(computer.error().unwrap().starts_with("Value 18446744073709551616"));
+}
+
+#[test]
+fn testRun()
+{
+    // Add 2 inputs and output the sum, twice.
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,100,3,101,1,100,101,102,4,102,1105,1,0]);
+    assert_eq!(computer.run(), State::WaitingInput);
+    computer.pushInput(1);
+    assert_eq!(computer.runFor(1), State::Running);
+    assert_eq!(computer.run(), State::WaitingInput);
+    computer.pushInput(2);
+    computer.pushInput(3);
+    assert!(computer.hasPendingInput());
+    assert_eq!(computer.run(), State::WaitingInput);
+    assert!(!computer.hasPendingInput());
+    computer.pushInput(4);
+    assert_eq!(computer.run(), State::WaitingInput);
+    assert_eq!(computer.takeOutput(), vec![3, 7]);
+    // Only what has not been taken is kept.
+    computer.pushInput(5);
+    assert_eq!(computer.input, vec![5]);
+    assert!(computer.output.is_empty());
+
+    computer.reset();
+    computer.loadCode(&vec![4,0,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.output, vec![4]);
+}
+
+#[test]
+fn testOutOfMemory()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![1,100000,0,3,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Address 100000 is out of memory");
+    assert_eq!(computer.cursor(), 0);
+    assert_eq!(computer.mem[3], 3);
+    assert!(computer.faulted());
+
+    computer.reset();
+    computer.loadCode(&vec![109,-5,204,0,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Address -5 is out of memory");
+    assert_eq!(computer.cursor(), 2);
+    assert!(computer.output.is_empty());
+
+    computer.reset();
+    computer.loadCode(&vec![1105,1,-1]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Jump to -1 is out of memory");
+}
+
+#[test]
+fn testSnapshot()
+{
+    // Output the running sum of the inputs, forever.
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,100,1,100,101,101,4,101,1105,1,0]);
+    computer.pushInput(1);
+    computer.pushInput(2);
+    assert_eq!(computer.run(), State::WaitingInput);
+    let snapshot = computer.snapshot();
+
+    computer.pushInput(3);
+    assert_eq!(computer.run(), State::WaitingInput);
+    assert_eq!(computer.output, vec![1, 3, 6]);
+
+    computer.restore(&snapshot);
+    assert_eq!(computer.output, vec![1, 3]);
+    computer.pushInput(10);
+    assert_eq!(computer.run(), State::WaitingInput);
+    assert_eq!(computer.output, vec![1, 3, 13]);
+    assert_eq!(computer.snapshot(), {
+        let mut other = IntCodeComputer::new();
+        other.restore(&computer.snapshot());
+        other.snapshot()
+    });
+}
+
+#[test]
+fn testInvalidOpcode()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![1101,1,1,0,42]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Invalid opcode 42");
+    assert_eq!(computer.cursor(), 4);
+
+    computer.reset();
+    computer.loadCode(&vec![301,0,0,0,99]);
+    assert_eq!(computer.pipe(None), None);
+    assert_eq!(computer.error().unwrap(), "Invalid opcode 301");
+}
+
+#[test]
+#[should_panic(expected = "Invalid opcode 42 at 4")]
+fn testEvalError()
+{
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![1101,1,1,0,42]);
+    computer.eval(None);
+}
+
+#[test]
+fn testBigOffset()
+{
+    // Move the relative base past the largest value.
+    let mut computer = IntCodeComputer::new();
+    computer.setArithmetic(Arithmetic::Big);
+    computer.loadCode(&vec![109,ValueType::MAX,109,1,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Overflow in relative base");
+    assert_eq!(computer.relativeBase(), ValueType::MAX);
+}
+
+#[test]
+fn testBigAddress()
+{
+    // A big product cannot be used as an address...
+    let mut computer = IntCodeComputer::new();
+    computer.setArithmetic(Arithmetic::Big);
+    computer.loadCode(&vec![1102,4294967296,4294967296,5,4,0,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Value 18446744073709551616 is too large to be used here");
+    assert!(!computer.faulted());
+
+    // ...or as an opcode.
+    computer.reset();
+    computer.loadCode(&vec![1102,4294967296,4294967296,4,99]);
+    assert_eq!(computer.run(), State::Halted);
+    assert_eq!(computer.error().unwrap(), "Value 18446744073709551616 is too large to be used here");
+}
@@ -0,0 +1,615 @@
+#![allow(dead_code)]
+
+// Concolic execution: run a program concretely on `IntCodeComputer',
+// while keeping track of which memory cells depend on the inputs and
+// how. Every jump whose condition depends on the inputs is recorded,
+// and a small local solver uses them to find inputs that reach a given
+// address.
+//
+// Add and mult wrap around on both sides, like
+// `Arithmetic::Wrapping'.
+
+use std::vec::Vec;
+use std::fmt;
+use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+
+use super::{ArgMode, Arithmetic, IntCodeComputer, OpCode, State, ValueType};
+
+// Expressions larger than this, counting shared parts every time they
+// are used, are not followed. The cell just keeps its concrete value.
+const MAX_SIZE: usize = 1000;
+
+// A value in terms of the inputs. Parts are shared, so that a value
+// added to itself does not copy it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr
+{
+    Const(ValueType),
+    // The input with this index.
+    Input(usize),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mult(Rc<Expr>, Rc<Expr>),
+    Less(Rc<Expr>, Rc<Expr>),
+    Equal(Rc<Expr>, Rc<Expr>),
+}
+
+impl Expr
+{
+    pub fn isConst(&self) -> bool
+    {
+        matches!(self, Expr::Const(_))
+    }
+
+    // Combine two expressions with `op', folding constants.
+    fn binary(op: u8, lhs: Expr, rhs: Expr) -> Expr
+    {
+        if let (Expr::Const(a), Expr::Const(b)) = (&lhs, &rhs)
+        {
+            return Expr::Const(apply(op, *a, *b));
+        }
+        match (op, &lhs, &rhs)
+        {
+            (1, Expr::Const(0), _) => rhs,
+            (1, _, Expr::Const(0)) => lhs,
+            (2, Expr::Const(1), _) => rhs,
+            (2, _, Expr::Const(1)) => lhs,
+            (2, Expr::Const(0), _) | (2, _, Expr::Const(0)) => Expr::Const(0),
+            (1, _, _) => Expr::Add(Rc::new(lhs), Rc::new(rhs)),
+            (2, _, _) => Expr::Mult(Rc::new(lhs), Rc::new(rhs)),
+            (7, _, _) => Expr::Less(Rc::new(lhs), Rc::new(rhs)),
+            _ => Expr::Equal(Rc::new(lhs), Rc::new(rhs)),
+        }
+    }
+
+    // The number of nodes, counting shared ones every time they are
+    // used. Stops counting somewhere past `limit'.
+    fn size(&self, limit: usize) -> usize
+    {
+        match self
+        {
+            Expr::Const(_) | Expr::Input(_) => 1,
+            Expr::Add(a, b) | Expr::Mult(a, b) | Expr::Less(a, b) | Expr::Equal(a, b) =>
+            {
+                let lhs = a.size(limit);
+                if lhs >= limit
+                {
+                    return lhs + 1;
+                }
+                lhs + b.size(limit - lhs) + 1
+            },
+        }
+    }
+
+    pub fn eval(&self, inputs: &[ValueType]) -> ValueType
+    {
+        match self
+        {
+            Expr::Const(x) => *x,
+            Expr::Input(i) => inputs.get(*i).cloned().unwrap_or(0),
+            Expr::Add(a, b) => apply(1, a.eval(inputs), b.eval(inputs)),
+            Expr::Mult(a, b) => apply(2, a.eval(inputs), b.eval(inputs)),
+            Expr::Less(a, b) => apply(7, a.eval(inputs), b.eval(inputs)),
+            Expr::Equal(a, b) => apply(8, a.eval(inputs), b.eval(inputs)),
+        }
+    }
+
+    // The inputs this depends on.
+    pub fn inputs(&self, result: &mut HashSet<usize>)
+    {
+        match self
+        {
+            Expr::Const(_) => {},
+            Expr::Input(i) => { result.insert(*i); },
+            Expr::Add(a, b) | Expr::Mult(a, b) | Expr::Less(a, b) | Expr::Equal(a, b) =>
+            {
+                a.inputs(result);
+                b.inputs(result);
+            },
+        }
+    }
+
+    // If this is `k * input + rest' for some constant `k', where `rest'
+    // does not depend on `input', return `k'.
+    fn coefficient(&self, input: usize) -> Option<ValueType>
+    {
+        match self
+        {
+            Expr::Const(_) => Some(0),
+            Expr::Input(i) => Some(if *i == input { 1 } else { 0 }),
+            Expr::Add(a, b) => a.coefficient(input)?.checked_add(b.coefficient(input)?),
+            Expr::Mult(a, b) => match (a.coefficient(input)?, b.coefficient(input)?)
+            {
+                (0, 0) => Some(0),
+                (k, 0) => if b.isConst() { k.checked_mul(b.eval(&[])) } else { None },
+                (0, k) => if a.isConst() { k.checked_mul(a.eval(&[])) } else { None },
+                _ => None,
+            },
+            Expr::Less(a, b) | Expr::Equal(a, b) =>
+            {
+                let mut used = HashSet::new();
+                a.inputs(&mut used);
+                b.inputs(&mut used);
+                if used.contains(&input) { None } else { Some(0) }
+            },
+        }
+    }
+}
+
+fn apply(op: u8, a: ValueType, b: ValueType) -> ValueType
+{
+    match op
+    {
+        1 => a.wrapping_add(b),
+        2 => a.wrapping_mul(b),
+        7 => (a < b) as ValueType,
+        _ => (a == b) as ValueType,
+    }
+}
+
+impl fmt::Display for Expr
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            Expr::Const(x) => write!(f, "{}", x),
+            Expr::Input(i) => write!(f, "input{}", i),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mult(a, b) => write!(f, "({} * {})", a, b),
+            Expr::Less(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Equal(a, b) => write!(f, "({} == {})", a, b),
+        }
+    }
+}
+
+// A jump whose condition depends on the inputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Branch
+{
+    pub address: usize,
+    // The jump is taken when this is non-zero for jmpt, or zero for
+    // jmpf.
+    pub condition: Expr,
+    pub jump_if_true: bool,
+    pub taken: bool,
+}
+
+impl Branch
+{
+    // Whether the branch goes the way it went, for other inputs.
+    fn holds(&self, inputs: &[ValueType]) -> bool
+    {
+        ((self.condition.eval(inputs) != 0) == self.jump_if_true) == self.taken
+    }
+}
+
+impl fmt::Display for Branch
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}: {} {}, {}", self.address, if self.jump_if_true { "jmpt" } else { "jmpf" },
+               self.condition, if self.taken { "taken" } else { "not taken" })
+    }
+}
+
+// What happened in one concolic run.
+#[derive(Clone, Debug)]
+pub struct Path
+{
+    pub inputs: Vec<ValueType>,
+    pub branches: Vec<Branch>,
+    pub outputs: Vec<Expr>,
+    // Every address executed, in order, without repeats.
+    pub visited: Vec<usize>,
+    // Places where a value depending on the inputs was used as an
+    // opcode, address or jump target, or grew larger than `MAX_SIZE',
+    // and just taken as is.
+    pub concretized: Vec<usize>,
+    pub state: State,
+}
+
+// Run `code' with `inputs' for at most `max_steps' instructions. When
+// the program wants more inputs than given, it gets 0, up to
+// `max_inputs' in total.
+//
+// The computer is stepped one instruction at a time, and before each
+// step the effect of the instruction on the inputs is worked out from
+// the memory.
+pub fn run(code: &[ValueType], entry: usize, inputs: &[ValueType], max_steps: usize,
+           max_inputs: usize) -> Path
+{
+    let mut computer = IntCodeComputer::new();
+    computer.setArithmetic(Arithmetic::Wrapping);
+    computer.loadCode(code);
+    computer.setCursor(entry);
+    let mut inputs = inputs.to_vec();
+    for x in &inputs
+    {
+        computer.pushInput(*x);
+    }
+
+    let mut shadow: HashMap<usize, Expr> = HashMap::new();
+    let mut input_count = 0;
+    let mut path = Path
+    {
+        inputs: vec![],
+        branches: vec![],
+        outputs: vec![],
+        visited: vec![],
+        concretized: vec![],
+        state: State::Running,
+    };
+    let mut seen: HashSet<usize> = HashSet::new();
+
+    for _ in 0..max_steps
+    {
+        let cursor = computer.cursor();
+        let op = match computer.mem.get(cursor).map(|x| OpCode::fromInt(*x))
+        {
+            Some(Ok(op)) => op,
+            _ =>
+            {
+                if shadow.contains_key(&cursor)
+                {
+                    path.concretized.push(cursor);
+                }
+                path.state = computer.runFor(1);
+                break;
+            },
+        };
+
+        if op.code == 3 && input_count >= inputs.len()
+        {
+            if inputs.len() >= max_inputs
+            {
+                path.state = State::WaitingInput;
+                break;
+            }
+            inputs.push(0);
+            computer.pushInput(0);
+        }
+
+        if seen.insert(cursor)
+        {
+            path.visited.push(cursor);
+        }
+
+        // The cell holding argument `i', and whether finding it
+        // needed a value that depends on the inputs. None if it is
+        // outside of memory.
+        let locate = |i: usize| -> Option<(usize, bool)>
+        {
+            let raw = cursor + i + 1;
+            let address = match op.arg_modes[i]
+            {
+                ArgMode::Position => *computer.mem.get(raw)?,
+                ArgMode::Immediate => { return Some((raw, false)); },
+                ArgMode::Relative => computer.mem.get(raw)?.checked_add(computer.relativeBase())?,
+            };
+            if address < 0 || address as usize >= computer.mem.len()
+            {
+                return None;
+            }
+            Some((address as usize, shadow.contains_key(&raw)))
+        };
+
+        // An argument outside of memory is a fault, except for the
+        // target of a jump that is not taken. Leave it to the
+        // computer.
+        let checked = if op.code == 5 || op.code == 6 { 1 } else { op.arg_count as usize };
+        if (0..checked).any(|i| locate(i).is_none())
+        {
+            path.state = computer.runFor(1);
+            break;
+        }
+        let cell = |i: usize| locate(i).unwrap();
+        let value = |i: usize| -> Expr
+        {
+            let address = cell(i).0;
+            shadow.get(&address).cloned().unwrap_or(Expr::Const(computer.mem[address]))
+        };
+
+        let concretized = shadow.contains_key(&cursor)
+            || (0..checked).any(|i| cell(i).1);
+        let mut write: Option<(usize, Expr)> = None;
+        match op.code
+        {
+            1 | 2 | 7 | 8 =>
+            {
+                write = Some((cell(2).0, Expr::binary(op.code, value(0), value(1))));
+            },
+            3 =>
+            {
+                write = Some((cell(0).0, Expr::Input(input_count)));
+                input_count += 1;
+            },
+            4 => { path.outputs.push(value(0)); },
+            5 | 6 =>
+            {
+                let condition = value(0);
+                if !condition.isConst()
+                {
+                    let jump_if_true = op.code == 5;
+                    let taken = (condition.eval(&inputs) != 0) == jump_if_true;
+                    path.branches.push(Branch { address: cursor, condition, jump_if_true, taken });
+                }
+                if locate(1).is_some_and(|(address, depends)| depends || shadow.contains_key(&address))
+                {
+                    path.concretized.push(cursor);
+                }
+            },
+            9 if !value(0).isConst() => { path.concretized.push(cursor); },
+            _ => {},
+        }
+        if concretized
+        {
+            path.concretized.push(cursor);
+        }
+
+        let state = computer.runFor(1);
+        if let Some((address, expr)) = write
+        {
+            if expr.isConst()
+            {
+                shadow.remove(&address);
+            }
+            else if expr.size(MAX_SIZE) > MAX_SIZE
+            {
+                shadow.remove(&address);
+                path.concretized.push(cursor);
+            }
+            else
+            {
+                shadow.insert(address, expr);
+            }
+        }
+        path.state = state;
+        if state != State::Running
+        {
+            break;
+        }
+    }
+
+    inputs.truncate(input_count);
+    path.inputs = inputs;
+    path
+}
+
+// Values of `input' worth trying to make `expr' become `target'.
+fn candidates(expr: &Expr, input: usize, target: ValueType, inputs: &[ValueType])
+    -> Vec<ValueType>
+{
+    let current = inputs.get(input).cloned().unwrap_or(0);
+    let mut result = vec![];
+
+    // Comparisons: make the two sides meet.
+    if let Expr::Less(a, b) | Expr::Equal(a, b) = expr
+    {
+        let difference = Expr::Add(a.clone(), Rc::new(Expr::Mult(b.clone(), Rc::new(Expr::Const(-1)))));
+        let is_less = matches!(expr, Expr::Less(_, _));
+        let goal = match (is_less, target != 0)
+        {
+            (true, true) => -1,
+            (true, false) | (false, true) => 0,
+            (false, false) => 1,
+        };
+        result.extend(candidates(&difference, input, goal, inputs));
+    }
+
+    // Linear in the input: solve for it.
+    if let Some(k) = expr.coefficient(input)
+    {
+        if k != 0
+        {
+            let mut zeroed = inputs.to_vec();
+            if zeroed.len() <= input
+            {
+                zeroed.resize(input + 1, 0);
+            }
+            zeroed[input] = 0;
+            let rest = expr.eval(&zeroed);
+            let needed = target.wrapping_sub(rest);
+            if needed.checked_rem(k) == Some(0)
+            {
+                result.extend(needed.checked_div(k));
+            }
+        }
+    }
+
+    result.extend(current.checked_add(1));
+    result.extend(current.checked_sub(1));
+    result.extend(vec![0, 1, -1]);
+    result
+}
+
+// Look for inputs that make the first `prefix' branches of `path' go
+// the same way, and branch `prefix' go the other way.
+fn flip(path: &Path, prefix: usize) -> Option<Vec<ValueType>>
+{
+    let branch = &path.branches[prefix];
+    let wanted = Branch { taken: !branch.taken, ..branch.clone() };
+    let target = if wanted.taken == wanted.jump_if_true { 1 } else { 0 };
+
+    let mut used = HashSet::new();
+    branch.condition.inputs(&mut used);
+    let mut used: Vec<usize> = used.into_iter().collect();
+    used.sort();
+
+    for input in used
+    {
+        for value in candidates(&branch.condition, input, target, &path.inputs)
+        {
+            let mut inputs = path.inputs.clone();
+            if inputs.len() <= input
+            {
+                inputs.resize(input + 1, 0);
+            }
+            inputs[input] = value;
+            if wanted.holds(&inputs) && path.branches[..prefix].iter().all(|b| b.holds(&inputs))
+            {
+                return Some(inputs);
+            }
+        }
+    }
+    None
+}
+
+// Look for inputs that make the program reach `target', starting from
+// `inputs'. Branches are flipped one at a time, last one first, and
+// each new set of inputs is run again to see where it goes. Gives up
+// after `max_runs' runs.
+pub fn reach(code: &[ValueType], entry: usize, target: usize, inputs: &[ValueType],
+             max_steps: usize, max_inputs: usize, max_runs: usize) -> Option<Vec<ValueType>>
+{
+    let mut todo: Vec<Vec<ValueType>> = vec![inputs.to_vec()];
+    let mut tried: HashSet<Vec<ValueType>> = HashSet::new();
+    // Branch directions already explored, as the list of (address,
+    // taken) leading up to them.
+    let mut explored: HashSet<Vec<(usize, bool)>> = HashSet::new();
+
+    let mut runs = 0;
+    while let Some(inputs) = todo.pop()
+    {
+        if runs >= max_runs
+        {
+            break;
+        }
+        if !tried.insert(inputs.clone())
+        {
+            continue;
+        }
+        runs += 1;
+
+        let path = run(code, entry, &inputs, max_steps, max_inputs);
+        if path.visited.contains(&target)
+        {
+            return Some(path.inputs);
+        }
+
+        let mut directions: Vec<(usize, bool)> = vec![];
+        for (i, branch) in path.branches.iter().enumerate()
+        {
+            let mut flipped = directions.clone();
+            flipped.push((branch.address, !branch.taken));
+            directions.push((branch.address, branch.taken));
+            if explored.insert(flipped)
+            {
+                if let Some(inputs) = flip(&path, i)
+                {
+                    todo.push(inputs);
+                }
+            }
+        }
+    }
+    None
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testExpr()
+{
+    let x = Expr::Input(0);
+    let e = Expr::binary(1, Expr::binary(2, Expr::Const(3), x.clone()), Expr::Const(4));
+    assert_eq!(e.to_string(), "((3 * input0) + 4)");
+    assert_eq!(e.eval(&[5]), 19);
+    assert_eq!(e.coefficient(0), Some(3));
+    assert_eq!(e.coefficient(1), Some(0));
+    assert_eq!(Expr::binary(2, x.clone(), x.clone()).coefficient(0), None);
+    assert_eq!(Expr::binary(1, Expr::Const(2), Expr::Const(3)), Expr::Const(5));
+    assert_eq!(Expr::binary(2, Expr::Const(1), x.clone()), x);
+}
+
+// input x
+// eq x, 42, ok
+// jmpt ok, :good
+// output 0
+// halt
+// good:
+// output 1
+// halt
+#[cfg(test)]
+const PASSWORD: [ValueType; 15] = [3,26,1008,26,42,27,1005,27,12,104,0,99,104,1,99];
+
+#[test]
+fn testRun()
+{
+    let code = PASSWORD.to_vec();
+    let path = run(&code, 0, &[], 100, 10);
+    assert_eq!(path.inputs, vec![0]);
+    assert_eq!(path.state, State::Halted);
+    assert_eq!(path.branches.len(), 1);
+    assert_eq!(path.branches[0].to_string(), "6: jmpt (input0 == 42), not taken");
+    assert_eq!(path.outputs, vec![Expr::Const(0)]);
+
+    assert!(path.branches[0].holds(&[1]));
+    assert!(!path.branches[0].holds(&[42]));
+}
+
+#[test]
+fn testOutOfRange()
+{
+    // Output from below the memory.
+    let code = vec![109,-5,204,0,99];
+    let path = run(&code, 0, &[], 100, 10);
+    assert_eq!(path.state, State::Halted);
+    assert!(path.outputs.is_empty());
+    // The target of a jump that is not taken is never read.
+    let code = vec![6,5,-1,104,7,99];
+    let path = run(&code, 0, &[], 100, 10);
+    assert_eq!(path.outputs, vec![Expr::Const(7)]);
+
+    // Values that do not fit are left out.
+    let expr = Expr::Mult(Rc::new(Expr::Input(0)), Rc::new(Expr::Const(-1)));
+    let values = candidates(&expr, 0, ValueType::MIN, &[ValueType::MAX]);
+    assert_eq!(values, vec![ValueType::MAX - 1, 0, 1, -1]);
+}
+
+#[test]
+fn testLargeExpr()
+{
+    // input x
+    // loop:
+    // add x, x, x
+    // jmpt 1, :loop
+    let code = vec![3,9,1,9,9,9,1105,1,2,0];
+    let path = run(&code, 0, &[1], 200, 10);
+    assert_eq!(path.state, State::Running);
+    assert!(path.concretized.contains(&2));
+
+    // Add and mult wrap around, like in the expressions.
+    let code = vec![1101,ValueType::MAX,1,7,4,7,99,0];
+    let path = run(&code, 0, &[], 100, 10);
+    assert_eq!(path.state, State::Halted);
+    assert_eq!(path.outputs, vec![Expr::Const(ValueType::MIN)]);
+}
+
+#[test]
+fn testReach()
+{
+    let code = PASSWORD.to_vec();
+    assert_eq!(reach(&code, 0, 12, &[], 100, 10, 10), Some(vec![42]));
+
+    // input a
+    // input b
+    // mult a, 3, t
+    // add t, b, t
+    // eq t, 100, c
+    // jmpf c, :bad
+    // less a, b, c
+    // jmpf c, :bad
+    // output 1
+    // halt
+    // bad:
+    // output 0
+    // halt
+    let code = vec![3,43,3,44,1002,43,3,45,1,45,44,45,1008,45,100,46,1006,46,
+                    29,7,43,44,46,1006,46,29,104,1,99,104,0,99];
+    let inputs = reach(&code, 0, 26, &[], 100, 10, 20).unwrap();
+    assert_eq!(inputs[0] * 3 + inputs[1], 100);
+    assert!(inputs[0] < inputs[1]);
+
+    // The middle of an instruction is never reached.
+    assert_eq!(reach(&code, 0, 27, &[], 100, 10, 20), None);
+}
@@ -6,60 +6,834 @@ use std::env;
 use std::process::exit;
 use std::fs;
 
-mod intcode;
+use advent_2019::intcode::{self, assembly, search, fuzz, image, analysis, concolic};
+
+use std::ops::Range;
+
+use intcode::{Arithmetic, IntCodeComputer, State, Tracer, ValueType};
+use assembly::SymbolTable;
+use image::Image;
+use analysis::{describeCode, disassemble};
 
 fn usage(name: &String)
 {
-    println!("Usage: {name} FILE
+    println!("Usage: {name} [OPTIONS] FILE
+       {name} search [SEARCH_OPTIONS] FILE
+       {name} fuzz [FUZZ_OPTIONS]
+       {name} concolic [CONCOLIC_OPTIONS] FILE
+
+Run load intcode from FILE and run it. Input is read from stdin as the
+program asks for it, one number per line, and output is written to
+stdout as it is produced.
+
+FILE can also be a binary image written by the assembler, or
+inssembly source, which is assembled before running. Source is assumed
+if its name ends with .ins, or if it contains letters.
+
+Options:
+  -a, --ascii              Talk to the program in ASCII: each line of
+                           input is sent as character codes followed by
+                           a newline, and outputs below 128 are printed
+                           as characters.
+  -p, --poke ADDR=VALUE    Set memory at ADDR to VALUE before running.
+                           Can be given multiple times.
+  -i, --input-file INPUT   Take input from file INPUT instead of stdin.
+  -I, --input VALUES       Take input from VALUES instead of stdin. In
+                           normal mode it's a comma-separated list of
+                           numbers; in ASCII mode it's sent as a line.
+  -d, --dump OUTPUT        Write memory to file OUTPUT after the run.
+  -r, --range BEGIN..END   Only dump memory from BEGIN to END
+                           (exclusive). Can be given multiple times.
+  -s, --state              Print the cursor and relative base to stderr
+                           after the run.
+  --arithmetic MODE        How to deal with overflow in add and mult.
+                           MODE is one of checked (default), wrapping,
+                           and big.
+  --asm                    Treat FILE as inssembly source.
+  -S, --symbols SYMBOLS    Load labels and variable names from the
+                           symbol file SYMBOLS, as written by the
+                           assembler. They are used to report errors and
+                           traces. Not needed if FILE is inssembly.
+  -t, --trace              Print every instruction to stderr before
+                           executing it.
+  -h, --help               Show this message.
+
+The search command runs FILE with every combination of the values
+given by --vary and --vary-input, and prints the first one that meets
+all the --expect conditions.
+
+Search options:
+  --vary ADDR=BEGIN..END   Try values from BEGIN to END (exclusive) for
+                           memory at ADDR.
+  --vary-input I=BEGIN..END
+                           Try values from BEGIN to END (exclusive) for
+                           input number I, counting from 0.
+  --expect-mem ADDR=VALUE  Memory at ADDR must be VALUE after the run.
+  --expect-output VALUE    The last output must be VALUE.
+  --expect-halt            The program must halt.
+  --distinct               Only try combinations of different values.
+  -j, --threads N          Search with N threads.
+  --max-steps N            Stop each run after N instructions.
+  -p, -I, --asm and -S work as above.
+
+The fuzz command runs random programs on the emulator in all its
+modes, and on a simple reference implementation. Disagreements are
+printed with the program cut down to a small reproducer.
+
+Fuzz options:
+  --seed N                 Start from seed N. Default is the time.
+  --count N                Run N programs. Default is 1000.
+  --length N               Make programs of up to N instructions.
+                           Default is 16.
+  --max-steps N            Stop each run after N instructions. Default
+                           is 1000.
+
+The concolic command runs FILE while tracking how values depend on the
+inputs, and prints the jumps whose conditions depend on them. Inputs
+that are not given are 0. Add and mult wrap around.
+
+Concolic options:
+  --reach ADDR             Instead, look for inputs that make the
+                           program reach ADDR, which can also be a
+                           label, and print them.
+  --max-steps N            Stop each run after N instructions.
+  --max-inputs N           Give the program at most N inputs.
+  --max-runs N             Give up looking after N runs.
+  -I, --asm and -S work as above.", name=name);
+}
+
+struct Options
+{
+    filename: String,
+    ascii: bool,
+    pokes: Vec<(usize, ValueType)>,
+    // Input given on the command line or in a file. Without it, input
+    // is read interactively from stdin.
+    input: Option<Vec<ValueType>>,
+    dump: Option<String>,
+    ranges: Vec<(usize, usize)>,
+    print_state: bool,
+    arithmetic: Arithmetic,
+    assembly: bool,
+    symbol_file: Option<String>,
+    trace: bool,
+}
+
+fn parseNumber<T: std::str::FromStr>(s: &str) -> Result<T, String>
+{
+    s.trim().parse::<T>().map_err(|_| format!("Invalid number: {}", s))
+}
+
+fn parsePoke(s: &str) -> Result<(usize, ValueType), String>
+{
+    let mut parts = s.splitn(2, '=');
+    let addr = parseNumber(parts.next().unwrap())?;
+    let value = parseNumber(parts.next().ok_or(format!("Invalid poke: {}", s))?)?;
+    Ok((addr, value))
+}
+
+fn parseRange(s: &str) -> Result<(usize, usize), String>
+{
+    let mut parts = s.splitn(2, "..");
+    let begin = parseNumber(parts.next().unwrap())?;
+    let end = parseNumber(parts.next().ok_or(format!("Invalid range: {}", s))?)?;
+    if begin > end
+    {
+        return Err(format!("Invalid range: {}", s));
+    }
+    Ok((begin, end))
+}
+
+// Turn input given all at once into values for the program.
+fn parseGivenInput(text: &str, ascii: bool) -> Result<Vec<ValueType>, String>
+{
+    if ascii
+    {
+        return Ok(text.bytes().map(|c| c as ValueType).collect());
+    }
+
+    text.split(|c: char| c == ',' || c.is_whitespace()).filter(|part| !part.is_empty())
+        .map(|part| parseNumber(part).map_err(|_| format!("Invalid input: {}", part)))
+        .collect()
+}
+
+fn defaultOptions() -> Options
+{
+    Options
+    {
+        filename: String::new(),
+        ascii: false,
+        pokes: vec![],
+        input: None,
+        dump: None,
+        ranges: vec![],
+        print_state: false,
+        arithmetic: Arithmetic::Checked,
+        assembly: false,
+        symbol_file: None,
+        trace: false,
+    }
+}
+
+fn parseArgs(args: &Vec<String>) -> Result<Options, String>
+{
+    let mut options = defaultOptions();
+    let mut input_file: Option<String> = None;
+    let mut input_inline: Option<String> = None;
+    let mut filename: Option<String> = None;
+
+    let mut i = 1;
+    while i < args.len()
+    {
+        let arg = &args[i][..];
+        let mut value = || -> Result<String, String>
+        {
+            i += 1;
+            args.get(i).cloned().ok_or(format!("Option {} needs a value", arg))
+        };
+
+        match arg
+        {
+            "-a" | "--ascii" => { options.ascii = true; },
+            "-p" | "--poke" => { options.pokes.push(parsePoke(&value()?)?); },
+            "-i" | "--input-file" => { input_file = Some(value()?); },
+            "-I" | "--input" => { input_inline = Some(value()?); },
+            "-d" | "--dump" => { options.dump = Some(value()?); },
+            "-r" | "--range" => { options.ranges.push(parseRange(&value()?)?); },
+            "-s" | "--state" => { options.print_state = true; },
+            "--asm" => { options.assembly = true; },
+            "-S" | "--symbols" => { options.symbol_file = Some(value()?); },
+            "-t" | "--trace" => { options.trace = true; },
+            "--arithmetic" =>
+            {
+                options.arithmetic = match &value()?[..]
+                {
+                    "wrapping" => Arithmetic::Wrapping,
+                    "checked" => Arithmetic::Checked,
+                    "big" => Arithmetic::Big,
+                    mode => { return Err(format!("Unknown arithmetic mode: {}", mode)); },
+                };
+            },
+            _ =>
+            {
+                if arg.starts_with('-') || filename.is_some()
+                {
+                    return Err(format!("Unexpected argument: {}", arg));
+                }
+                filename = Some(String::from(arg));
+            },
+        }
+        i += 1;
+    }
+
+    options.filename = filename.ok_or(String::from("Missing FILE"))?;
+
+    if input_file.is_some() && input_inline.is_some()
+    {
+        return Err(String::from("Only one of --input-file and --input can be used"));
+    }
+    if let Some(f) = input_file
+    {
+        let text = fs::read_to_string(&f).map_err(|_| format!("Failed to read file {}.", f))?;
+        options.input = Some(parseGivenInput(&text, options.ascii)?);
+    }
+    if let Some(text) = input_inline
+    {
+        let text = if options.ascii { text + "\n" } else { text };
+        options.input = Some(parseGivenInput(&text, options.ascii)?);
+    }
+    Ok(options)
+}
+
+// Read the next line from stdin for the program. Returns None at end
+// of input.
+fn readInput(ascii: bool) -> Result<Option<Vec<ValueType>>, String>
+{
+    loop
+    {
+        let mut line = String::new();
+        let size = io::stdin().lock().read_line(&mut line)
+            .map_err(|_| String::from("Failed to read input"))?;
+        if size == 0
+        {
+            return Ok(None);
+        }
+
+        let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+        if ascii
+        {
+            return Ok(Some(line.bytes().map(|c| c as ValueType)
+                           .chain(vec![b'\n' as ValueType]).collect()));
+        }
+
+        let line = line.trim();
+        if line.is_empty()
+        {
+            continue;
+        }
+        return line.parse::<ValueType>().map(|x| Some(vec![x]))
+            .map_err(|_| format!("Invalid input: {}", line));
+    }
+}
+
+fn writeOutput(computer: &mut IntCodeComputer, ascii: bool)
+{
+    let values = computer.bigOutput();
+    computer.takeOutput();
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for value in values
+    {
+        match value.toValue()
+        {
+            Some(x) if ascii && (0..128).contains(&x) =>
+            {
+                write!(out, "{}", x as u8 as char).expect("Failed to write output");
+            },
+            _ => { writeln!(out, "{}", value).expect("Failed to write output"); },
+        }
+    }
+    out.flush().expect("Failed to write output");
+}
+
+// Load the program in FILE, which can be Intcode text, a binary image,
+// or inssembly that is assembled here. Symbols from the symbol file
+// replace any that came with the program.
+fn loadProgram(options: &Options) -> Result<Image, String>
+{
+    let bytes = fs::read(&options.filename)
+        .map_err(|_| format!("Failed to read file {}.", options.filename))?;
+
+    let mut image = Image::loadFile(&options.filename, &bytes, options.assembly)?;
+
+    if let Some(filename) = &options.symbol_file
+    {
+        let text = fs::read_to_string(filename)
+            .map_err(|_| format!("Failed to read file {}.", filename))?;
+        image.symbols = Some(text.parse()?);
+    }
+    Ok(image)
+}
+
+struct PrintTracer
+{
+    symbols: Option<SymbolTable>,
+}
+
+impl Tracer for PrintTracer
+{
+    fn step(&mut self, computer: &IntCodeComputer)
+    {
+        eprintln!("{}", disassemble(&computer.mem, computer.cursor(), &self.symbols));
+    }
+}
+
+fn dumpMemory(computer: &IntCodeComputer, options: &Options, filename: &str)
+    -> Result<(), String>
+{
+    let join = |values: &[ValueType]| -> String
+    {
+        values.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(",")
+    };
+
+    let content = if options.ranges.is_empty()
+    {
+        // Trailing zeros are not interesting.
+        let end = computer.mem.iter().rposition(|x| *x != 0).map(|i| i + 1).unwrap_or(0);
+        join(&computer.mem[..end]) + "\n"
+    }
+    else
+    {
+        let mut lines = String::new();
+        for (begin, end) in &options.ranges
+        {
+            if *end > computer.mem.len()
+            {
+                return Err(format!("Range {}..{} is out of memory", begin, end));
+            }
+            lines += &format!("{}..{}: {}\n", begin, end, join(&computer.mem[*begin..*end]));
+        }
+        lines
+    };
+
+    fs::write(filename, content).map_err(|_| format!("Failed to write file {}.", filename))
+}
+
+struct SearchOptions
+{
+    program: Options,
+    slots: Vec<(search::Slot, Range<ValueType>)>,
+    expect_mem: Vec<(usize, ValueType)>,
+    expect_output: Option<ValueType>,
+    expect_halt: bool,
+    distinct: bool,
+    threads: Option<usize>,
+    max_steps: Option<usize>,
+}
+
+// Parse `ADDR=BEGIN..END'.
+fn parseVary(s: &str) -> Result<(usize, Range<ValueType>), String>
+{
+    let (index, range) = s.split_once('=').ok_or(format!("Invalid range: {}", s))?;
+    let (begin, end) = range.split_once("..").ok_or(format!("Invalid range: {}", s))?;
+    let begin: ValueType = parseNumber(begin)?;
+    let end: ValueType = parseNumber(end)?;
+    if begin > end
+    {
+        return Err(format!("Invalid range: {}", s));
+    }
+    Ok((parseNumber(index)?, begin..end))
+}
+
+// Parse the arguments after `search'.
+fn parseSearchArgs(args: &Vec<String>) -> Result<SearchOptions, String>
+{
+    let mut options = SearchOptions
+    {
+        program: defaultOptions(),
+        slots: vec![],
+        expect_mem: vec![],
+        expect_output: None,
+        expect_halt: false,
+        distinct: false,
+        threads: None,
+        max_steps: None,
+    };
+    let mut filename: Option<String> = None;
+
+    let mut i = 2;
+    while i < args.len()
+    {
+        let arg = &args[i][..];
+        let mut value = || -> Result<String, String>
+        {
+            i += 1;
+            args.get(i).cloned().ok_or(format!("Option {} needs a value", arg))
+        };
+
+        match arg
+        {
+            "--vary" =>
+            {
+                let (address, range) = parseVary(&value()?)?;
+                options.slots.push((search::Slot::Memory(address), range));
+            },
+            "--vary-input" =>
+            {
+                let (index, range) = parseVary(&value()?)?;
+                options.slots.push((search::Slot::Input(index), range));
+            },
+            "--expect-mem" => { options.expect_mem.push(parsePoke(&value()?)?); },
+            "--expect-output" => { options.expect_output = Some(parseNumber(&value()?)?); },
+            "--expect-halt" => { options.expect_halt = true; },
+            "--distinct" => { options.distinct = true; },
+            "-j" | "--threads" => { options.threads = Some(parseNumber(&value()?)?); },
+            "--max-steps" => { options.max_steps = Some(parseNumber(&value()?)?); },
+            "-p" | "--poke" => { options.program.pokes.push(parsePoke(&value()?)?); },
+            "-I" | "--input" => { options.program.input = Some(parseGivenInput(&value()?, false)?); },
+            "--asm" => { options.program.assembly = true; },
+            "-S" | "--symbols" => { options.program.symbol_file = Some(value()?); },
+            _ =>
+            {
+                if arg.starts_with('-') || filename.is_some()
+                {
+                    return Err(format!("Unexpected argument: {}", arg));
+                }
+                filename = Some(String::from(arg));
+            },
+        }
+        i += 1;
+    }
+
+    options.program.filename = filename.ok_or(String::from("Missing FILE"))?;
+    if options.slots.is_empty()
+    {
+        return Err(String::from("Nothing to search, use --vary or --vary-input"));
+    }
+    Ok(options)
+}
+
+fn runSearch(options: &SearchOptions) -> Result<(), String>
+{
+    let image = loadProgram(&options.program)?;
+    let symbols = image.symbols;
+    let mut code = image.code;
+    for (addr, value) in &options.program.pokes
+    {
+        if *addr >= code.len()
+        {
+            return Err(format!("Cannot poke {}, memory size is {}", addr, code.len()));
+        }
+        code[*addr] = *value;
+    }
+
+    let mut search = search::Search::new(&code);
+    search.setEntry(image.entry);
+    for (slot, range) in &options.slots
+    {
+        match slot
+        {
+            search::Slot::Memory(address) =>
+            {
+                if *address >= code.len()
+                {
+                    return Err(format!("Cannot vary {}, memory size is {}", address, code.len()));
+                }
+                search.addMemory(*address, range.clone());
+            },
+            search::Slot::Input(index) => { search.addInput(*index, range.clone()); },
+        }
+    }
+    if let Some(input) = &options.program.input
+    {
+        search.setInputs(input);
+    }
+    search.setDistinct(options.distinct);
+    if let Some(threads) = options.threads
+    {
+        search.setThreads(threads);
+    }
+    if let Some(steps) = options.max_steps
+    {
+        search.setMaxSteps(steps);
+    }
+
+    let found = search.find(
+        |computer|
+        {
+            (!options.expect_halt || computer.halted())
+                && options.expect_mem.iter().all(
+                    |(addr, value)| computer.mem.get(*addr) == Some(value))
+                && options.expect_output.iter().all(
+                    |value| computer.output.last() == Some(value))
+        })?;
+
+    let found = found.ok_or(String::from("No match found"))?;
+    for (slot, value) in found.slots.iter().zip(&found.values)
+    {
+        match slot
+        {
+            search::Slot::Memory(address) =>
+            {
+                let name = match &symbols
+                {
+                    Some(table) => table.describeData(*address),
+                    None => format!("[{}]", address),
+                };
+                println!("{} = {}", name, value);
+            },
+            search::Slot::Input(index) => { println!("input {} = {}", index, value); },
+        }
+    }
+    Ok(())
+}
+
+struct FuzzOptions
+{
+    seed: u64,
+    count: usize,
+    length: usize,
+    max_steps: usize,
+}
+
+// Parse the arguments after `fuzz'.
+fn parseFuzzArgs(args: &Vec<String>) -> Result<FuzzOptions, String>
+{
+    let mut options = FuzzOptions
+    {
+        seed: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+            .map(|t| t.as_secs()).unwrap_or(0),
+        count: 1000,
+        length: 16,
+        max_steps: 1000,
+    };
+
+    let mut i = 2;
+    while i < args.len()
+    {
+        let arg = &args[i][..];
+        let value = args.get(i + 1).ok_or(format!("Option {} needs a value", arg))?;
+        match arg
+        {
+            "--seed" => { options.seed = parseNumber(value)?; },
+            "--count" => { options.count = parseNumber(value)?; },
+            "--length" => { options.length = parseNumber(value)?; },
+            "--max-steps" => { options.max_steps = parseNumber(value)?; },
+            _ => { return Err(format!("Unexpected argument: {}", arg)); },
+        }
+        i += 2;
+    }
+    Ok(options)
+}
+
+fn runFuzz(options: &FuzzOptions) -> Result<(), String>
+{
+    eprintln!("Running {} programs from seed {}...", options.count, options.seed);
+    let found = fuzz::fuzz(&fuzz::engines(), options.seed, options.count, options.length,
+                           options.max_steps);
+    for divergence in &found
+    {
+        println!("Engine {} with seed {}: {}", divergence.engine, divergence.seed,
+                 divergence.description);
+        println!("{}\n", divergence.case);
+    }
+
+    if found.is_empty()
+    {
+        eprintln!("No disagreements.");
+        Ok(())
+    }
+    else
+    {
+        Err(format!("Found {} disagreements", found.len()))
+    }
+}
+
+struct ConcolicOptions
+{
+    program: Options,
+    // An address or a label.
+    reach: Option<String>,
+    max_steps: usize,
+    max_inputs: usize,
+    max_runs: usize,
+}
+
+// Parse the arguments after `concolic'.
+fn parseConcolicArgs(args: &Vec<String>) -> Result<ConcolicOptions, String>
+{
+    let mut options = ConcolicOptions
+    {
+        program: defaultOptions(),
+        reach: None,
+        max_steps: 1_000_000,
+        max_inputs: 100,
+        max_runs: 1000,
+    };
+    let mut filename: Option<String> = None;
+
+    let mut i = 2;
+    while i < args.len()
+    {
+        let arg = &args[i][..];
+        let mut value = || -> Result<String, String>
+        {
+            i += 1;
+            args.get(i).cloned().ok_or(format!("Option {} needs a value", arg))
+        };
+
+        match arg
+        {
+            "--reach" => { options.reach = Some(value()?); },
+            "--max-steps" => { options.max_steps = parseNumber(&value()?)?; },
+            "--max-inputs" => { options.max_inputs = parseNumber(&value()?)?; },
+            "--max-runs" => { options.max_runs = parseNumber(&value()?)?; },
+            "-I" | "--input" => { options.program.input = Some(parseGivenInput(&value()?, false)?); },
+            "--asm" => { options.program.assembly = true; },
+            "-S" | "--symbols" => { options.program.symbol_file = Some(value()?); },
+            _ =>
+            {
+                if arg.starts_with('-') || filename.is_some()
+                {
+                    return Err(format!("Unexpected argument: {}", arg));
+                }
+                filename = Some(String::from(arg));
+            },
+        }
+        i += 1;
+    }
+
+    options.program.filename = filename.ok_or(String::from("Missing FILE"))?;
+    Ok(options)
+}
+
+fn runConcolic(options: &ConcolicOptions) -> Result<(), String>
+{
+    let image = loadProgram(&options.program)?;
+    let inputs = options.program.input.clone().unwrap_or(vec![]);
 
-Run load intcode from FILE and run it. Take input from stdin (one
-number per line) and output to stdout. If there's no input, you need
-to press ctrl+d.", name=name);
+    if let Some(target) = &options.reach
+    {
+        let label = image.symbols.as_ref().and_then(|table| table.labels.get(target));
+        let address = match label
+        {
+            Some(address) => *address,
+            None => parseNumber(target)?,
+        };
+        let found = concolic::reach(&image.code, image.entry, address, &inputs, options.max_steps,
+                                    options.max_inputs, options.max_runs);
+        return match found
+        {
+            Some(inputs) =>
+            {
+                println!("{}", inputs.iter().map(|x| x.to_string())
+                         .collect::<Vec<String>>().join(","));
+                Ok(())
+            },
+            None => Err(format!("Could not reach {}", describeCode(address, &image.symbols))),
+        };
+    }
+
+    let path = concolic::run(&image.code, image.entry, &inputs, options.max_steps,
+                             options.max_inputs);
+    println!("Ran with inputs {:?}, ended {:?}.", path.inputs, path.state);
+    println!("Jumps that depend on input:");
+    for branch in &path.branches
+    {
+        println!("    {}", branch);
+    }
+    if !path.concretized.is_empty()
+    {
+        println!("Input used as an opcode, address or jump target, or too large to follow, at:");
+        for address in &path.concretized
+        {
+            println!("    {}", describeCode(*address, &image.symbols));
+        }
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), String>
 {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2
+    if args.len() > 1 && (args[1] == "-h" || args[1] == "--help")
     {
         usage(&args[0]);
-        exit(1);
+        return Ok(());
     }
 
-    if args[1] == "-h" || args[1] == "--help"
+    if args.len() > 1 && args[1] == "search"
     {
-        usage(&args[0]);
-        return Ok(());
+        let options = match parseSearchArgs(&args)
+        {
+            Ok(options) => options,
+            Err(msg) =>
+            {
+                eprintln!("{}", msg);
+                usage(&args[0]);
+                exit(1);
+            },
+        };
+        return runSearch(&options);
+    }
+
+    if args.len() > 1 && args[1] == "fuzz"
+    {
+        let options = match parseFuzzArgs(&args)
+        {
+            Ok(options) => options,
+            Err(msg) =>
+            {
+                eprintln!("{}", msg);
+                usage(&args[0]);
+                exit(1);
+            },
+        };
+        return runFuzz(&options);
     }
 
-    let source_raw = fs::read_to_string(&args[1])
-        .map_err(|_| format!("Failed to read file {}.", args[1]))?;
-    let source = source_raw.trim();
+    if args.len() > 1 && args[1] == "concolic"
+    {
+        let options = match parseConcolicArgs(&args)
+        {
+            Ok(options) => options,
+            Err(msg) =>
+            {
+                eprintln!("{}", msg);
+                usage(&args[0]);
+                exit(1);
+            },
+        };
+        return runConcolic(&options);
+    }
+
+    let options = match parseArgs(&args)
+    {
+        Ok(options) => options,
+        Err(msg) =>
+        {
+            eprintln!("{}", msg);
+            usage(&args[0]);
+            exit(1);
+        },
+    };
+
+    let image = loadProgram(&options)?;
+    let symbols = image.symbols.clone();
+
+    let mut computer = IntCodeComputer::new();
+    computer.setArithmetic(options.arithmetic);
+    image.loadInto(&mut computer);
+    if options.trace
+    {
+        computer.setTracer(Some(Box::new(PrintTracer { symbols: symbols.clone() })));
+    }
+    for (addr, value) in &options.pokes
+    {
+        if *addr >= computer.mem.len()
+        {
+            return Err(format!("Cannot poke {}, memory size is {}", addr, computer.mem.len()));
+        }
+        computer.mem[*addr] = *value;
+    }
+
+    if let Some(input) = &options.input
+    {
+        for x in input
+        {
+            computer.pushInput(*x);
+        }
+    }
+
+    let mut result = Ok(());
+    loop
+    {
+        let state = computer.run();
+        writeOutput(&mut computer, options.ascii);
 
-    let mut input_raw = String::new();
-    io::stdin().lock().read_to_string(&mut input_raw).expect("Failed to read input");
-    let mut computer = intcode::IntCodeComputer::new();
+        if state == State::Halted
+        {
+            break;
+        }
 
-    let input: Vec<intcode::ValueType> = input_raw.trim().lines().map(
-        |line|
+        let input = if options.input.is_some() { None } else { readInput(options.ascii)? };
+        match input
         {
-            if let Ok(x) = line.parse::<intcode::ValueType>()
+            Some(input) =>
             {
-                x
-            }
-            else
+                for x in input
+                {
+                    computer.pushInput(x);
+                }
+            },
+            None =>
             {
-                panic!("Invalid input: {}", line);
-            }
-        }).collect();
+                result = Err(String::from("Program needs more input"));
+                break;
+            },
+        }
+    }
 
-    computer.loadCode(&(intcode::parse(&source[..])));
-    computer.eval(if input.is_empty() {None} else {Some(&input)});
+    if let Some(error) = computer.error()
+    {
+        let line = match image.line(computer.cursor())
+        {
+            Some(line) => format!(", line {}", line),
+            None => String::new(),
+        };
+        result = Err(format!("{} at {}{}", error, describeCode(computer.cursor(), &symbols), line));
+    }
 
-    for x in computer.output
+    if options.print_state
     {
-        println!("{}", x);
+        eprintln!("Cursor: {}", computer.cursor());
+        eprintln!("Relative base: {}", computer.relativeBase());
     }
 
-    Ok(())
+    if let Some(filename) = &options.dump
+    {
+        dumpMemory(&computer, &options, filename)?;
+    }
+    result
 }
@@ -0,0 +1,826 @@
+#![allow(dead_code)]
+
+// Differential testing of `IntCodeComputer'. Random but well-formed
+// programs are run on an independent reference interpreter and on a
+// number of engines, and anything they disagree on is reported, with
+// the program cut down to a small reproducer.
+
+use std::vec::Vec;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::convert::TryFrom;
+
+use super::{Arithmetic, IntCodeComputer, State, ValueType};
+
+// Size of the memory of `IntCodeComputer'.
+const MEMORY_SIZE: usize = 8192;
+
+// Xorshift64*. Good enough for making up programs, and reproducible
+// from the seed.
+pub struct Rng
+{
+    state: u64,
+}
+
+impl Rng
+{
+    pub fn new(seed: u64) -> Self
+    {
+        // The state must never be zero.
+        Rng { state: seed ^ 0x9e3779b97f4a7c15 }
+    }
+
+    pub fn nextU64(&mut self) -> u64
+    {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    // A number in `0..n'.
+    pub fn below(&mut self, n: usize) -> usize
+    {
+        (self.nextU64() % (n as u64)) as usize
+    }
+
+    // A number in `begin..end', or `begin' if that is empty.
+    pub fn range(&mut self, begin: ValueType, end: ValueType) -> ValueType
+    {
+        if end <= begin
+        {
+            return begin;
+        }
+        begin + (self.nextU64() % ((end - begin) as u64)) as ValueType
+    }
+
+    // True with a chance of 1 in `n'.
+    pub fn oneIn(&mut self, n: usize) -> bool
+    {
+        self.below(n) == 0
+    }
+}
+
+// A program with its inputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case
+{
+    pub code: Vec<ValueType>,
+    pub input: Vec<ValueType>,
+}
+
+impl fmt::Display for Case
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let join = |values: &Vec<ValueType>| values.iter().map(|x| x.to_string())
+            .collect::<Vec<String>>().join(",");
+        writeln!(f, "Code: {}", join(&self.code))?;
+        write!(f, "Input: {}", join(&self.input))
+    }
+}
+
+// Number of cells after the code that the generated programs use for
+// data.
+const DATA_SIZE: usize = 16;
+
+// Make up a program of `length' instructions followed by a halt and
+// some data. Arguments mostly point into the program and its data,
+// and jumps mostly go to the start of an instruction, so that the
+// programs do something interesting before they crash, if at all.
+pub fn generate(rng: &mut Rng, length: usize) -> Case
+{
+    let codes: Vec<ValueType> = (0..length).map(
+        |_| [1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 7, 8, 5, 6][rng.below(15)]).collect();
+    let arg_count = |code: ValueType| match code
+    {
+        1 | 2 | 7 | 8 => 3,
+        5 | 6 => 2,
+        _ => 1,
+    };
+
+    let mut starts: Vec<usize> = vec![];
+    let mut size = 0;
+    for code in &codes
+    {
+        starts.push(size);
+        size += arg_count(*code) + 1;
+    }
+    starts.push(size);
+    let data_start = size + 1;
+    let total = data_start + DATA_SIZE;
+
+    let mut code: Vec<ValueType> = vec![];
+    for op in &codes
+    {
+        let count = arg_count(*op);
+        let mut instruction = *op;
+        let mut args: Vec<ValueType> = vec![];
+        for i in 0..count
+        {
+            let writes = i == count - 1 && [1, 2, 3, 7, 8].contains(op);
+            let jump = i == 1 && (*op == 5 || *op == 6);
+            let mode = if writes
+            {
+                // Writes are rarely immediate, and then go into the
+                // instruction itself.
+                [0, 0, 0, 2, 1][rng.below(5)]
+            }
+            else if jump || *op == 9
+            {
+                if rng.oneIn(4) { rng.below(3) } else { 1 }
+            }
+            else
+            {
+                rng.below(3)
+            };
+
+            let arg = match mode
+            {
+                0 if writes && !rng.oneIn(4) => (data_start + rng.below(DATA_SIZE)) as ValueType,
+                0 => rng.below(total) as ValueType,
+                1 if jump => starts[rng.below(starts.len())] as ValueType,
+                1 if *op == 9 => rng.range(0, 4),
+                1 => rng.range(-10, 10),
+                _ => rng.below(DATA_SIZE) as ValueType,
+            };
+            instruction += (mode as ValueType) * [100, 1000, 10000][i];
+            args.push(arg);
+        }
+        code.push(instruction);
+        code.append(&mut args);
+    }
+    code.push(99);
+
+    for _ in 0..DATA_SIZE
+    {
+        let value = if rng.oneIn(8)
+        {
+            // Big enough to overflow now and then.
+            rng.range(-(1 << 40), 1 << 40)
+        }
+        else
+        {
+            rng.range(-20, 20)
+        };
+        code.push(value);
+    }
+
+    let input = (0..rng.below(5)).map(|_| rng.range(-100, 100)).collect();
+    Case { code, input }
+}
+
+// How a run ended.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status
+{
+    // Still running after the step limit.
+    Running,
+    WaitingInput,
+    Halted,
+    // Stopped with an error, like an invalid opcode.
+    Error,
+    // Tried to access memory outside of the computer.
+    Fault,
+    // The engine panicked. The reference never does.
+    Panic,
+}
+
+// Everything that is compared between runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome
+{
+    pub status: Status,
+    pub mem: Vec<ValueType>,
+    pub output: Vec<ValueType>,
+    pub cursor: usize,
+    pub relative_base: ValueType,
+    // Outputs that do not fit in a `ValueType', by index, in full.
+    // `output' has their lowest bits.
+    pub big_output: Vec<(usize, String)>,
+    // Whether some value did not even fit in an `i128', so that the
+    // reference could not follow. Only the reference knows this.
+    pub overflow: bool,
+}
+
+fn fits(x: i128) -> bool
+{
+    ValueType::try_from(x).is_ok()
+}
+
+// A straightforward Intcode interpreter, written separately from
+// `IntCodeComputer' so that they do not share bugs. Values are kept in
+// an `i128', and `arithmetic' says what happens to results that do not
+// fit in a `ValueType': they wrap around, stop the program with an
+// error, or are kept. Kept values cannot be used as opcodes,
+// addresses, jump targets or relative bases.
+pub fn reference(case: &Case, max_steps: usize, arithmetic: Arithmetic) -> Outcome
+{
+    let mut mem: Vec<i128> = vec![0; MEMORY_SIZE];
+    for (i, x) in case.code.iter().enumerate()
+    {
+        mem[i] = *x as i128;
+    }
+    let mut ip: usize = 0;
+    let mut base: ValueType = 0;
+    let mut input = case.input.iter();
+    let mut output: Vec<i128> = vec![];
+    let mut overflow = false;
+
+    let status = 'run:
+    {
+        for _ in 0..max_steps
+        {
+            if ip >= MEMORY_SIZE
+            {
+                break 'run Status::Fault;
+            }
+            if !fits(mem[ip])
+            {
+                break 'run Status::Error;
+            }
+            let instruction = mem[ip] as ValueType;
+            let op = instruction.rem_euclid(100);
+            let count = match op
+            {
+                1 | 2 | 7 | 8 => 3,
+                5 | 6 => 2,
+                3 | 4 | 9 => 1,
+                99 => 0,
+                _ => { break 'run Status::Error; },
+            };
+            if instruction < 0 || (0..3).any(|i| instruction / [100, 1000, 10000][i] % 10 > 2)
+            {
+                break 'run Status::Error;
+            }
+            if ip + count >= MEMORY_SIZE
+            {
+                break 'run Status::Fault;
+            }
+
+            // Address of argument `i', or how the program stops.
+            let address = |i: usize, mem: &Vec<i128>| -> Result<usize, Status>
+            {
+                let raw = mem[ip + i + 1];
+                let address = match instruction / [100, 1000, 10000][i] % 10
+                {
+                    1 => (ip + i + 1) as i128,
+                    _ if !fits(raw) => { return Err(Status::Error); },
+                    0 => raw,
+                    _ => raw + base as i128,
+                };
+                if address < 0 || address >= MEMORY_SIZE as i128
+                {
+                    Err(Status::Fault)
+                }
+                else
+                {
+                    Ok(address as usize)
+                }
+            };
+            macro_rules! arg
+            {
+                ($i:expr) =>
+                {
+                    match address($i, &mem)
+                    {
+                        Ok(a) => mem[a],
+                        Err(status) => { break 'run status; },
+                    }
+                }
+            }
+            macro_rules! dest
+            {
+                ($i:expr) =>
+                {
+                    match address($i, &mem)
+                    {
+                        Ok(a) => a,
+                        Err(status) => { break 'run status; },
+                    }
+                }
+            }
+
+            match op
+            {
+                1 | 2 =>
+                {
+                    let (lhs, rhs, to) = (arg!(0), arg!(1), dest!(2));
+                    let result = if arithmetic == Arithmetic::Wrapping
+                    {
+                        let (lhs, rhs) = (lhs as ValueType, rhs as ValueType);
+                        Some(if op == 1 { lhs.wrapping_add(rhs) } else { lhs.wrapping_mul(rhs) } as i128)
+                    }
+                    else if op == 1
+                    {
+                        lhs.checked_add(rhs)
+                    }
+                    else
+                    {
+                        lhs.checked_mul(rhs)
+                    };
+                    let result = match result
+                    {
+                        Some(x) => x,
+                        None =>
+                        {
+                            overflow = true;
+                            break 'run Status::Running;
+                        },
+                    };
+                    if arithmetic == Arithmetic::Checked && !fits(result)
+                    {
+                        break 'run Status::Error;
+                    }
+                    mem[to] = result;
+                    ip += 4;
+                },
+                3 =>
+                {
+                    let to = dest!(0);
+                    match input.next()
+                    {
+                        Some(x) => { mem[to] = *x as i128; },
+                        None => { break 'run Status::WaitingInput; },
+                    }
+                    ip += 2;
+                },
+                4 =>
+                {
+                    output.push(arg!(0));
+                    ip += 2;
+                },
+                5 | 6 =>
+                {
+                    if (arg!(0) != 0) == (op == 5)
+                    {
+                        let target = arg!(1);
+                        if !fits(target)
+                        {
+                            break 'run Status::Error;
+                        }
+                        if target < 0
+                        {
+                            break 'run Status::Fault;
+                        }
+                        ip = target as usize;
+                    }
+                    else
+                    {
+                        ip += 3;
+                    }
+                },
+                7 | 8 =>
+                {
+                    let (lhs, rhs, to) = (arg!(0), arg!(1), dest!(2));
+                    let result = if op == 7 { lhs < rhs } else { lhs == rhs };
+                    mem[to] = result as i128;
+                    ip += 4;
+                },
+                9 =>
+                {
+                    let delta = arg!(0);
+                    if !fits(delta)
+                    {
+                        break 'run Status::Error;
+                    }
+                    base = match base.checked_add(delta as ValueType)
+                    {
+                        Some(x) => x,
+                        None if arithmetic == Arithmetic::Wrapping => base.wrapping_add(delta as ValueType),
+                        None => { break 'run Status::Error; },
+                    };
+                    ip += 2;
+                },
+                99 => { break 'run Status::Halted; },
+                _ => unreachable!(),
+            }
+        }
+        Status::Running
+    };
+
+    Outcome
+    {
+        status,
+        mem: mem.iter().map(|x| *x as ValueType).collect(),
+        output: output.iter().map(|x| *x as ValueType).collect(),
+        cursor: ip,
+        relative_base: base,
+        big_output: output.iter().enumerate().filter(|(_, x)| !fits(**x))
+            .map(|(i, x)| (i, x.to_string())).collect(),
+        overflow,
+    }
+}
+
+// Something that runs Intcode.
+pub trait Engine
+{
+    fn name(&self) -> String;
+
+    // Run `case' for at most `max_steps' instructions.
+    fn run(&self, case: &Case, max_steps: usize) -> Outcome;
+
+    // What the engine does with results that do not fit, so that the
+    // reference can do the same.
+    fn arithmetic(&self) -> Arithmetic { Arithmetic::Wrapping }
+}
+
+fn outcomeOf(computer: &IntCodeComputer, state: State) -> Outcome
+{
+    let status = match state
+    {
+        _ if computer.faulted() => Status::Fault,
+        _ if computer.error().is_some() => Status::Error,
+        State::Running => Status::Running,
+        State::WaitingInput => Status::WaitingInput,
+        State::Halted => Status::Halted,
+    };
+    Outcome
+    {
+        status,
+        mem: computer.mem.clone(),
+        output: computer.output.clone(),
+        cursor: computer.cursor(),
+        relative_base: computer.relativeBase(),
+        big_output: computer.bigOutput().iter().enumerate().filter(|(_, x)| x.toValue().is_none())
+            .map(|(i, x)| (i, x.to_string())).collect(),
+        overflow: false,
+    }
+}
+
+// Run `f' on a new computer loaded with `case', and report a panic as
+// such.
+fn runComputer<F>(case: &Case, arithmetic: Arithmetic, f: F) -> Outcome
+where F: FnOnce(&mut IntCodeComputer) -> State
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(
+        ||
+        {
+            let mut computer = IntCodeComputer::new();
+            computer.setArithmetic(arithmetic);
+            computer.loadCode(&case.code);
+            for x in &case.input
+            {
+                computer.pushInput(*x);
+            }
+            let state = f(&mut computer);
+            outcomeOf(&computer, state)
+        }));
+
+    result.unwrap_or_else(
+        |_| Outcome
+        {
+            status: Status::Panic,
+            mem: vec![],
+            output: vec![],
+            cursor: 0,
+            relative_base: 0,
+            big_output: vec![],
+            overflow: false,
+        })
+}
+
+// `IntCodeComputer' with `runFor()'.
+pub struct Computer(pub Arithmetic);
+
+impl Engine for Computer
+{
+    fn name(&self) -> String
+    {
+        format!("{:?}", self.0).to_lowercase()
+    }
+
+    fn run(&self, case: &Case, max_steps: usize) -> Outcome
+    {
+        runComputer(case, self.0, |computer| computer.runFor(max_steps))
+    }
+
+    fn arithmetic(&self) -> Arithmetic
+    {
+        self.0
+    }
+}
+
+// `IntCodeComputer', one instruction at a time.
+pub struct Stepper;
+
+impl Engine for Stepper
+{
+    fn name(&self) -> String
+    {
+        String::from("stepper")
+    }
+
+    fn run(&self, case: &Case, max_steps: usize) -> Outcome
+    {
+        runComputer(case, Arithmetic::Wrapping, |computer|
+        {
+            let mut state = State::Running;
+            for _ in 0..max_steps
+            {
+                state = computer.runFor(1);
+                if state != State::Running
+                {
+                    break;
+                }
+            }
+            state
+        })
+    }
+}
+
+// All the engines there are.
+pub fn engines() -> Vec<Box<dyn Engine>>
+{
+    vec![Box::new(Computer(Arithmetic::Wrapping)),
+         Box::new(Computer(Arithmetic::Checked)),
+         Box::new(Computer(Arithmetic::Big)),
+         Box::new(Stepper)]
+}
+
+// Describe how `outcome' differs from `expected', or return None if it
+// does not.
+pub fn compare(expected: &Outcome, outcome: &Outcome) -> Option<String>
+{
+    if expected.overflow
+    {
+        return None;
+    }
+    if expected.status != outcome.status
+    {
+        return Some(format!("status is {:?} instead of {:?}", outcome.status, expected.status));
+    }
+    if expected.output != outcome.output
+    {
+        return Some(format!("output is {:?} instead of {:?}", outcome.output, expected.output));
+    }
+    if expected.big_output != outcome.big_output
+    {
+        return Some(format!("big outputs are {:?} instead of {:?}",
+                            outcome.big_output, expected.big_output));
+    }
+    if expected.cursor != outcome.cursor
+    {
+        return Some(format!("cursor is {} instead of {}", outcome.cursor, expected.cursor));
+    }
+    if expected.relative_base != outcome.relative_base
+    {
+        return Some(format!("relative base is {} instead of {}",
+                            outcome.relative_base, expected.relative_base));
+    }
+    let cell = (0..expected.mem.len().max(outcome.mem.len()))
+        .find(|i| expected.mem.get(*i) != outcome.mem.get(*i));
+    if let Some(i) = cell
+    {
+        return Some(format!("memory at {} is {:?} instead of {:?}",
+                            i, outcome.mem.get(i), expected.mem.get(i)));
+    }
+    None
+}
+
+// Whether `engine' disagrees with the reference on `case'.
+pub fn diverges(engine: &dyn Engine, case: &Case, max_steps: usize) -> Option<String>
+{
+    compare(&reference(case, max_steps, engine.arithmetic()), &engine.run(case, max_steps))
+}
+
+fn cells(case: &mut Case, which: usize) -> &mut Vec<ValueType>
+{
+    if which == 0 { &mut case.code } else { &mut case.input }
+}
+
+// Cut `case' down as much as possible, while `engine' still disagrees
+// with the reference on it.
+pub fn minimize(engine: &dyn Engine, case: &Case, max_steps: usize) -> Case
+{
+    let fails = |case: &Case| diverges(engine, case, max_steps).is_some();
+    let mut best = case.clone();
+
+    loop
+    {
+        let mut progress = false;
+
+        // Code first, then the inputs.
+        for which in 0..2
+        {
+            // Drop runs of cells, long ones first.
+            let mut size = (cells(&mut best, which).len() / 2).max(1);
+            while size > 0
+            {
+                let mut start = 0;
+                while start < cells(&mut best, which).len()
+                {
+                    let mut candidate = best.clone();
+                    let values = cells(&mut candidate, which);
+                    let end = (start + size).min(values.len());
+                    values.drain(start..end);
+                    if fails(&candidate)
+                    {
+                        best = candidate;
+                        progress = true;
+                    }
+                    else
+                    {
+                        start += size;
+                    }
+                }
+                size /= 2;
+            }
+
+            // Make values smaller.
+            for i in 0..cells(&mut best, which).len()
+            {
+                let value = cells(&mut best, which)[i];
+                for smaller in [0, 1, value / 2]
+                {
+                    if smaller.abs() >= value.abs()
+                    {
+                        continue;
+                    }
+                    let mut candidate = best.clone();
+                    cells(&mut candidate, which)[i] = smaller;
+                    if fails(&candidate)
+                    {
+                        best = candidate;
+                        progress = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !progress
+        {
+            return best;
+        }
+    }
+}
+
+// A disagreement found by `fuzz()'.
+pub struct Divergence
+{
+    pub engine: String,
+    pub seed: u64,
+    pub description: String,
+    pub case: Case,
+}
+
+// Run `count' random programs with up to `length' instructions on all
+// `engines', and return the disagreements, minimized. Program `i' is
+// made from seed `seed + i', so it can be reproduced on its own.
+pub fn fuzz(engines: &Vec<Box<dyn Engine>>, seed: u64, count: usize, length: usize,
+            max_steps: usize) -> Vec<Divergence>
+{
+    let mut found = vec![];
+    for i in 0..count as u64
+    {
+        let mut rng = Rng::new(seed.wrapping_add(i));
+        let size = 1 + rng.below(length.max(1));
+        let case = generate(&mut rng, size);
+        for engine in engines
+        {
+            if diverges(engine.as_ref(), &case, max_steps).is_some()
+            {
+                let small = minimize(engine.as_ref(), &case, max_steps);
+                found.push(Divergence
+                {
+                    engine: engine.name(),
+                    seed: seed.wrapping_add(i),
+                    description: diverges(engine.as_ref(), &small, max_steps).unwrap(),
+                    case: small,
+                });
+            }
+        }
+    }
+    found
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testRng()
+{
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    for _ in 0..100
+    {
+        assert_eq!(a.nextU64(), b.nextU64());
+        let x = a.range(-5, 5);
+        assert!((-5..5).contains(&x));
+        b.range(-5, 5);
+    }
+    assert_ne!(Rng::new(1).nextU64(), Rng::new(2).nextU64());
+    assert_eq!(a.range(3, 3), 3);
+}
+
+#[test]
+fn testReference()
+{
+    let case = Case { code: vec![1,0,0,0,99], input: vec![] };
+    let outcome = reference(&case, 100, Arithmetic::Wrapping);
+    assert_eq!(outcome.status, Status::Halted);
+    assert_eq!(outcome.mem[..5].to_vec(), vec![2,0,0,0,99]);
+
+    // Relative mode and input.
+    let case = Case { code: vec![109,5,203,2,204,2,99], input: vec![7] };
+    let outcome = reference(&case, 100, Arithmetic::Wrapping);
+    assert_eq!(outcome.output, vec![7]);
+    assert_eq!(outcome.relative_base, 5);
+
+    let case = Case { code: vec![3,0,99], input: vec![] };
+    assert_eq!(reference(&case, 100, Arithmetic::Wrapping).status, Status::WaitingInput);
+    let case = Case { code: vec![1105,1,0], input: vec![] };
+    assert_eq!(reference(&case, 100, Arithmetic::Wrapping).status, Status::Running);
+    let case = Case { code: vec![1,-1,0,0,99], input: vec![] };
+    assert_eq!(reference(&case, 100, Arithmetic::Wrapping).status, Status::Fault);
+    let case = Case { code: vec![42], input: vec![] };
+    assert_eq!(reference(&case, 100, Arithmetic::Wrapping).status, Status::Error);
+
+    // Square 2^32 and output it, in each arithmetic.
+    let case = Case { code: vec![1102,4294967296,4294967296,7,4,7,99,0], input: vec![] };
+    let outcome = reference(&case, 100, Arithmetic::Wrapping);
+    assert_eq!(outcome.output, vec![0]);
+    let outcome = reference(&case, 100, Arithmetic::Checked);
+    assert_eq!((outcome.status, outcome.cursor), (Status::Error, 0));
+    let outcome = reference(&case, 100, Arithmetic::Big);
+    assert_eq!(outcome.output, vec![0]);
+    assert_eq!(outcome.big_output, vec![(0, String::from("18446744073709551616"))]);
+    assert!(!outcome.overflow);
+
+    // Square 2^40 until it does not fit in an i128.
+    let case = Case { code: vec![2,7,7,7,1105,1,0,1099511627776], input: vec![] };
+    assert!(reference(&case, 100, Arithmetic::Big).overflow);
+    assert!(!reference(&case, 100, Arithmetic::Checked).overflow);
+}
+
+#[test]
+fn testAgree()
+{
+    let found = fuzz(&engines(), 2019, 300, 12, 200);
+    for divergence in &found
+    {
+        println!("{} ({}): {}\n{}", divergence.engine, divergence.seed,
+                 divergence.description, divergence.case);
+    }
+    assert!(found.is_empty());
+
+    // Faults are compared like everything else.
+    for code in &[vec![1,-1,0,0,99], vec![109,-5,204,0,99], vec![1105,1,-1], vec![3,-1]]
+    {
+        let case = Case { code: code.clone(), input: vec![] };
+        assert_eq!(reference(&case, 100, Arithmetic::Wrapping).status, Status::Fault);
+        for engine in engines()
+        {
+            assert_eq!(diverges(engine.as_ref(), &case, 100), None, "{}: {}", engine.name(), case);
+        }
+    }
+
+    // So are overflows, and what comes of them.
+    for code in &[vec![1102,4294967296,4294967296,7,4,7,99,0],
+                  vec![1102,4294967296,4294967296,11,1007,11,0,11,4,11,99,0],
+                  vec![109,9223372036854775807,109,1,99],
+                  vec![1102,4294967296,4294967296,5,4,0,99],
+                  vec![1102,4294967296,4294967296,4,0,99]]
+    {
+        let case = Case { code: code.clone(), input: vec![] };
+        for engine in engines()
+        {
+            assert_eq!(diverges(engine.as_ref(), &case, 100), None, "{}: {}", engine.name(), case);
+        }
+    }
+}
+
+// Gets the last output wrong.
+struct Broken;
+
+impl Engine for Broken
+{
+    fn name(&self) -> String
+    {
+        String::from("broken")
+    }
+
+    fn run(&self, case: &Case, max_steps: usize) -> Outcome
+    {
+        let mut outcome = Computer(Arithmetic::Wrapping).run(case, max_steps);
+        if let Some(x) = outcome.output.last_mut()
+        {
+            *x += 1;
+        }
+        outcome
+    }
+}
+
+#[test]
+fn testMinimize()
+{
+    let found = fuzz(&vec![Box::new(Broken)], 1, 50, 12, 200);
+    assert!(!found.is_empty());
+    for divergence in &found
+    {
+        // Not much more than an output.
+        assert!(divergence.case.code.len() <= 12, "{}", divergence.case);
+        assert!(divergence.case.code.iter().any(|x| x.rem_euclid(100) == 4));
+        assert!(diverges(&Broken, &divergence.case, 200).is_some());
+    }
+}
@@ -0,0 +1,407 @@
+#![allow(dead_code)]
+
+// A binary format for Intcode programs. An image starts with a header:
+//
+//   magic    4 bytes, “INTC”
+//   version  2 bytes, little endian
+//   entry    varint, the address to start running from
+//   count    varint, the number of sections
+//
+// followed by the sections. Each section is a 4-byte tag, the size of
+// its content as a varint, and the content. Sections are:
+//
+//   CODE  the number of cells as a varint, then each cell as a
+//         zigzag-encoded varint. Always present.
+//   SYMS  a symbol file, as written by the assembler. Optional.
+//   DEBG  the number of entries as a varint, then pairs of address
+//         and source line as varints. Optional.
+//
+// Readers skip sections they do not know about. Varints are LEB128:
+// 7 bits at a time, lowest first, with the high bit set on all but the
+// last byte.
+
+use std::vec::Vec;
+
+use super::{IntCodeComputer, ValueType};
+use super::assembly::{self, SymbolTable};
+
+pub const MAGIC: &[u8; 4] = b"INTC";
+pub const VERSION: u16 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image
+{
+    pub code: Vec<ValueType>,
+    pub entry: usize,
+    pub symbols: Option<SymbolTable>,
+    // Where the code of each source line starts, as (address, line)
+    // pairs.
+    pub lines: Vec<(usize, usize)>,
+}
+
+impl Image
+{
+    pub fn new(code: Vec<ValueType>) -> Self
+    {
+        Image { code, entry: 0, symbols: None, lines: vec![] }
+    }
+
+    // Assemble inssembly source, keeping the symbols and line numbers.
+    pub fn assemble(source: &str) -> Result<Self, String>
+    {
+        let statements = assembly::parse(source)?;
+        let (code, symbols) = assembly::assembleWithSymbols(&statements)?;
+        Ok(Image
+        {
+            code,
+            entry: 0,
+            symbols: Some(symbols),
+            lines: assembly::lineTable(&statements),
+        })
+    }
+
+    // Parse comma-separated Intcode.
+    pub fn fromText(text: &str) -> Result<Self, String>
+    {
+        let text = text.trim();
+        if text.is_empty()
+        {
+            return Ok(Image::new(vec![]));
+        }
+        let code = text.split(',').map(
+            |part| part.trim().parse::<ValueType>()
+                .map_err(|_| format!("Cannot convert '{}'.", part.trim())))
+            .collect::<Result<Vec<ValueType>, String>>()?;
+        Ok(Image::new(code))
+    }
+
+    // Comma-separated Intcode. Only the code survives.
+    pub fn toText(&self) -> String
+    {
+        self.code.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(",")
+    }
+
+    pub fn toBytes(&self) -> Vec<u8>
+    {
+        let mut sections: Vec<(&[u8; 4], Vec<u8>)> = vec![];
+
+        let mut code = vec![];
+        writeVarint(&mut code, self.code.len() as u64);
+        for x in &self.code
+        {
+            writeVarint(&mut code, zigzag(*x));
+        }
+        sections.push((b"CODE", code));
+
+        if let Some(symbols) = &self.symbols
+        {
+            sections.push((b"SYMS", symbols.to_string().into_bytes()));
+        }
+
+        if !self.lines.is_empty()
+        {
+            let mut debug = vec![];
+            writeVarint(&mut debug, self.lines.len() as u64);
+            for (address, line) in &self.lines
+            {
+                writeVarint(&mut debug, *address as u64);
+                writeVarint(&mut debug, *line as u64);
+            }
+            sections.push((b"DEBG", debug));
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        writeVarint(&mut bytes, self.entry as u64);
+        writeVarint(&mut bytes, sections.len() as u64);
+        for (tag, content) in sections
+        {
+            bytes.extend_from_slice(tag);
+            writeVarint(&mut bytes, content.len() as u64);
+            bytes.extend(content);
+        }
+        bytes
+    }
+
+    pub fn fromBytes(bytes: &[u8]) -> Result<Self, String>
+    {
+        if !isImage(bytes)
+        {
+            return Err(String::from("Not an Intcode image"));
+        }
+        let mut reader = Reader { bytes, pos: MAGIC.len() };
+        let version = u16::from_le_bytes([reader.byte()?, reader.byte()?]);
+        if version > VERSION
+        {
+            return Err(format!("Unsupported image version {}", version));
+        }
+
+        let entry = reader.varint()? as usize;
+        let mut image: Option<Image> = None;
+        let mut symbols: Option<SymbolTable> = None;
+        let mut lines = vec![];
+
+        for _ in 0..reader.varint()?
+        {
+            let tag = reader.take(4)?;
+            let size = reader.varint()? as usize;
+            let mut section = Reader { bytes: reader.take(size)?, pos: 0 };
+            match tag
+            {
+                b"CODE" =>
+                {
+                    let count = section.varint()? as usize;
+                    let mut code = Vec::with_capacity(count.min(size));
+                    for _ in 0..count
+                    {
+                        code.push(unzigzag(section.varint()?));
+                    }
+                    image = Some(Image::new(code));
+                },
+                b"SYMS" =>
+                {
+                    let text = std::str::from_utf8(section.bytes)
+                        .map_err(|_| String::from("Invalid symbol section"))?;
+                    symbols = Some(text.parse()?);
+                },
+                b"DEBG" =>
+                {
+                    for _ in 0..section.varint()?
+                    {
+                        lines.push((section.varint()? as usize, section.varint()? as usize));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        let mut image = image.ok_or(String::from("Image has no code"))?;
+        image.entry = entry;
+        image.symbols = symbols;
+        image.lines = lines;
+        Ok(image)
+    }
+
+    // Read an image in either format.
+    pub fn load(bytes: &[u8]) -> Result<Self, String>
+    {
+        if isImage(bytes)
+        {
+            Image::fromBytes(bytes)
+        }
+        else
+        {
+            let text = std::str::from_utf8(bytes)
+                .map_err(|_| String::from("Program is neither text nor an image"))?;
+            Image::fromText(text)
+        }
+    }
+
+    // Read the file `filename' with contents `bytes': an image,
+    // inssembly if `assembly' is set or `isAssembly()' says so, or
+    // else Intcode text.
+    pub fn loadFile(filename: &str, bytes: &[u8], assembly: bool) -> Result<Self, String>
+    {
+        if isImage(bytes)
+        {
+            return Image::fromBytes(bytes);
+        }
+        // Not trimmed, so that line numbers stay right.
+        let source = std::str::from_utf8(bytes)
+            .map_err(|_| format!("File {} is not text.", filename))?;
+        if assembly || isAssembly(filename, source)
+        {
+            Image::assemble(source)
+        }
+        else
+        {
+            Image::fromText(source)
+        }
+    }
+
+    // Load the code into `computer', and point it at the entry.
+    pub fn loadInto(&self, computer: &mut IntCodeComputer)
+    {
+        computer.loadCode(&self.code);
+        computer.setCursor(self.entry);
+    }
+
+    // The source line of the code at `address', if known.
+    pub fn line(&self, address: usize) -> Option<usize>
+    {
+        self.lines.iter().filter(|(addr, _)| *addr <= address)
+            .max_by_key(|(addr, _)| *addr).map(|(_, line)| *line)
+    }
+}
+
+pub fn isImage(bytes: &[u8]) -> bool
+{
+    bytes.starts_with(MAGIC)
+}
+
+// Whether `source', read from the file `filename', is inssembly rather
+// than Intcode text: its name ends with .ins, or it contains letters.
+pub fn isAssembly(filename: &str, source: &str) -> bool
+{
+    filename.ends_with(".ins") || source.chars().any(|c| c.is_alphabetic())
+}
+
+fn zigzag(x: ValueType) -> u64
+{
+    ((x << 1) ^ (x >> 63)) as u64
+}
+
+fn unzigzag(x: u64) -> ValueType
+{
+    ((x >> 1) as ValueType) ^ -((x & 1) as ValueType)
+}
+
+fn writeVarint(bytes: &mut Vec<u8>, mut x: u64)
+{
+    while x >= 0x80
+    {
+        bytes.push((x as u8) | 0x80);
+        x >>= 7;
+    }
+    bytes.push(x as u8);
+}
+
+struct Reader<'a>
+{
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a>
+{
+    fn take(&mut self, size: usize) -> Result<&'a [u8], String>
+    {
+        if self.bytes.len() - self.pos < size
+        {
+            return Err(String::from("Image is truncated"));
+        }
+        self.pos += size;
+        Ok(&self.bytes[self.pos - size..self.pos])
+    }
+
+    fn byte(&mut self) -> Result<u8, String>
+    {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String>
+    {
+        let mut x: u64 = 0;
+        for shift in (0..64).step_by(7)
+        {
+            let byte = self.byte()?;
+            x |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0
+            {
+                return Ok(x);
+            }
+        }
+        Err(String::from("Invalid varint in image"))
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testZigzag()
+{
+    for x in [0, 1, -1, 63, -64, 12345, ValueType::MAX, ValueType::MIN]
+    {
+        assert_eq!(unzigzag(zigzag(x)), x);
+    }
+    assert_eq!(zigzag(-1), 1);
+    assert_eq!(zigzag(1), 2);
+
+    let mut bytes = vec![];
+    writeVarint(&mut bytes, 300);
+    assert_eq!(bytes, vec![0xac, 0x02]);
+    writeVarint(&mut bytes, u64::MAX);
+    let mut reader = Reader { bytes: &bytes, pos: 0 };
+    assert_eq!(reader.varint(), Ok(300));
+    assert_eq!(reader.varint(), Ok(u64::MAX));
+    assert!(reader.varint().is_err());
+}
+
+#[test]
+fn testRoundTrip()
+{
+    let text = "109,-1,204,1,99,9223372036854775807,-9223372036854775808";
+    let image = Image::fromText(text).unwrap();
+    let bytes = image.toBytes();
+    assert!(isImage(&bytes));
+    assert_eq!(Image::load(&bytes).unwrap(), image);
+    assert_eq!(Image::load(&bytes).unwrap().toText(), text);
+    assert_eq!(Image::load(text.as_bytes()).unwrap(), image);
+    assert!(Image::fromText("1,x").is_err());
+
+    let mut image = Image::assemble("input x\nloop:\nadd x, -1, x\njmpt x, :loop\nhalt").unwrap();
+    image.entry = 2;
+    let loaded = Image::fromBytes(&image.toBytes()).unwrap();
+    assert_eq!(loaded, image);
+    assert_eq!(loaded.line(0), Some(1));
+    assert_eq!(loaded.line(7), Some(4));
+}
+
+#[test]
+fn testBadImage()
+{
+    let bytes = Image::new(vec![1,2,3]).toBytes();
+    assert!(Image::fromBytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Image::fromBytes(b"INTC\x02\x00\x00\x00").is_err());
+    assert!(Image::fromBytes(b"INTC\x01\x00\x00\x00").is_err());
+
+    // Unknown sections are skipped.
+    let mut bytes = b"INTC\x01\x00\x00\x02XTRA\x01\x00".to_vec();
+    bytes.extend_from_slice(b"CODE\x02\x01\x02");
+    assert_eq!(Image::fromBytes(&bytes).unwrap().code, vec![1]);
+}
+
+#[test]
+fn testLoadInto()
+{
+    let mut image = Image::new(vec![99,104,7,99]);
+    image.entry = 1;
+    let mut computer = IntCodeComputer::new();
+    image.loadInto(&mut computer);
+    computer.run();
+    assert_eq!(computer.output, vec![7]);
+}
+
+#[test]
+fn testLoadFile()
+{
+    let image = Image::assemble("output 7\nhalt").unwrap();
+    let text = image.toText();
+    assert_eq!(Image::loadFile("a.ins", b"output 7\nhalt", false).unwrap(), image);
+    assert_eq!(Image::loadFile("a.txt", b"output 7\nhalt", false).unwrap(), image);
+    assert_eq!(Image::loadFile("a.txt", text.as_bytes(), false).unwrap().code, image.code);
+    assert_eq!(Image::loadFile("a.ins", &image.toBytes(), false).unwrap(), image);
+    assert!(Image::loadFile("a.txt", text.as_bytes(), true).is_err());
+    assert!(Image::loadFile("a.ins", b"\xff", false).is_err());
+}
+
+#[test]
+fn testLoadImage()
+{
+    // The same program as text and as an image, with an entry point.
+    let text = "99,104,7,99";
+    let mut image = Image::fromText(text).unwrap();
+    image.entry = 1;
+    for bytes in [text.as_bytes().to_vec(), image.toBytes()]
+    {
+        let mut computer = IntCodeComputer::new();
+        computer.loadImage(&bytes).unwrap();
+        computer.run();
+        let expected = if isImage(&bytes) { vec![7] } else { vec![] };
+        assert_eq!(computer.output, expected);
+    }
+
+    let mut computer = IntCodeComputer::new();
+    assert!(computer.loadImage(b"1,x").is_err());
+    assert!(computer.loadImage(&[0xff, 0xfe]).is_err());
+}
@@ -0,0 +1,15 @@
+// The Intcode computer, with the tools around it in submodules. The
+// computer itself is in `computer', and is used as `intcode::X'.
+
+mod computer;
+pub mod bigint;
+pub mod assembly;
+pub mod image;
+pub mod network;
+pub mod threaded;
+pub mod search;
+pub mod fuzz;
+pub mod analysis;
+pub mod concolic;
+
+pub use self::computer::*;
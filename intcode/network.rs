@@ -0,0 +1,528 @@
+#![allow(dead_code)]
+
+use std::vec::Vec;
+use std::collections::VecDeque;
+
+use super::{IntCodeComputer, State, ValueType};
+
+// Something sent by one machine in a network. In a linked network the
+// payload is always a single value; in a packet network it is
+// everything after the address.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message
+{
+    pub from: usize,
+    // The index of the receiving machine. Anything not a valid index
+    // goes out of the network, and ends up in `Network::outbox()'.
+    pub to: ValueType,
+    pub payload: Vec<ValueType>,
+}
+
+// How outputs of machines become inputs of other machines.
+pub enum Routing
+{
+    // Fixed links, indexed by the sending machine. `None' sends
+    // outputs out of the network.
+    Links(Vec<Option<usize>>),
+    // Every machine outputs packets of `size' values. The first value
+    // is the address of the receiver. A machine that needs input and
+    // has none queued gets `idle_input' instead.
+    Packets
+    {
+        size: usize,
+        idle_input: ValueType,
+    },
+}
+
+// How the machines take turns.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Schedule
+{
+    // Run each machine for at most this many instructions in turn.
+    RoundRobin(usize),
+    // Run a machine until it blocks, and only run machines that have
+    // something to do.
+    EventDriven,
+}
+
+// Why `Network::run()' returned.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Outcome
+{
+    // All machines halted.
+    Halted,
+    // This machine stopped with an error, see its `error()'. The
+    // others are left where they were.
+    Failed(usize),
+    // A linked network where every machine that has not halted waits
+    // for input that nobody will send.
+    Deadlocked,
+    // A packet network where every machine keeps asking for input,
+    // and nothing is sent.
+    Idle,
+    // The monitor asked to stop.
+    Stopped,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Control
+{
+    Continue,
+    Stop,
+}
+
+// Watches the traffic of a network, like the NAT in a packet network.
+pub trait Monitor
+{
+    // Called for every message, before it is delivered.
+    fn message(&mut self, _message: &Message) -> Control
+    {
+        Control::Continue
+    }
+
+    // Called when the network is deadlocked or idle. Any returned
+    // messages are delivered, and the network carries on. Returning
+    // nothing ends the run.
+    fn idle(&mut self) -> Vec<Message>
+    {
+        vec![]
+    }
+}
+
+// A closure only observes messages.
+impl<F> Monitor for F where F: FnMut(&Message) -> Control
+{
+    fn message(&mut self, message: &Message) -> Control
+    {
+        self(message)
+    }
+}
+
+// The monitor of a network nobody watches.
+struct NoMonitor;
+impl Monitor for NoMonitor {}
+
+pub struct Network
+{
+    pub machines: Vec<IntCodeComputer>,
+    routing: Routing,
+    schedule: Schedule,
+    // Inputs not yet delivered to each machine.
+    queues: Vec<VecDeque<ValueType>>,
+    // Outputs of each machine that do not make a whole packet yet.
+    partial: Vec<Vec<ValueType>>,
+    // For packet networks, how many idle inputs each machine has
+    // received in a row.
+    starving: Vec<usize>,
+    // Whether each machine stopped at an input instruction last time
+    // it ran.
+    waiting: Vec<bool>,
+    outbox: Vec<Message>,
+}
+
+impl Network
+{
+    pub fn new(machines: Vec<IntCodeComputer>, routing: Routing) -> Self
+    {
+        let count = machines.len();
+        match &routing
+        {
+            Routing::Links(links) =>
+            {
+                assert_eq!(links.len(), count, "Need exactly one link per machine");
+            },
+            Routing::Packets { size, .. } =>
+            {
+                assert!(*size > 0, "Packets need at least the address");
+            },
+        }
+
+        Network
+        {
+            machines,
+            routing,
+            schedule: Schedule::EventDriven,
+            queues: vec![VecDeque::new(); count],
+            partial: vec![vec![]; count],
+            starving: vec![0; count],
+            waiting: vec![false; count],
+            outbox: vec![],
+        }
+    }
+
+    fn withCode(code: &[ValueType], count: usize, routing: Routing) -> Self
+    {
+        let machines = (0..count).map(
+            |_|
+            {
+                let mut computer = IntCodeComputer::new();
+                computer.loadCode(code);
+                computer
+            }).collect();
+        Self::new(machines, routing)
+    }
+
+    // `count' copies of `code', each sending to the next. The last
+    // one sends out of the network.
+    pub fn chain(code: &[ValueType], count: usize) -> Self
+    {
+        let links = (0..count).map(|i| if i + 1 < count { Some(i + 1) } else { None })
+            .collect();
+        Self::withCode(code, count, Routing::Links(links))
+    }
+
+    // Like `chain()', but the last one sends to the first one.
+    pub fn ring(code: &[ValueType], count: usize) -> Self
+    {
+        let links = (0..count).map(|i| Some((i + 1) % count)).collect();
+        Self::withCode(code, count, Routing::Links(links))
+    }
+
+    // `count' copies of `code' sending packets of `size' values to
+    // each other. Each machine first gets its own address as input.
+    pub fn packets(code: &[ValueType], count: usize, size: usize,
+                   idle_input: ValueType) -> Self
+    {
+        let mut network = Self::withCode(
+            code, count, Routing::Packets { size, idle_input });
+        for i in 0..count
+        {
+            network.send(i, i as ValueType);
+        }
+        network
+    }
+
+    pub fn setSchedule(&mut self, schedule: Schedule)
+    {
+        self.schedule = schedule;
+    }
+
+    // Queue an input for machine `to'.
+    pub fn send(&mut self, to: usize, value: ValueType)
+    {
+        self.queues[to].push_back(value);
+    }
+
+    // Inputs queued for machine `to' that it has not taken yet.
+    pub fn pending(&self, to: usize) -> &VecDeque<ValueType>
+    {
+        &self.queues[to]
+    }
+
+    // Messages that went out of the network.
+    pub fn outbox(&self) -> &Vec<Message>
+    {
+        &self.outbox
+    }
+
+    pub fn run(&mut self) -> Outcome
+    {
+        self.runWithMonitor(&mut NoMonitor)
+    }
+
+    pub fn runWithMonitor(&mut self, monitor: &mut dyn Monitor) -> Outcome
+    {
+        loop
+        {
+            for i in 0..self.machines.len()
+            {
+                if self.machines[i].halted()
+                {
+                    continue;
+                }
+
+                // Nothing would happen.
+                if self.schedule == Schedule::EventDriven && self.waiting[i]
+                    && self.queues[i].is_empty() && self.idleInput().is_none()
+                {
+                    continue;
+                }
+
+                self.runMachine(i);
+                let outputs = self.machines[i].takeOutput();
+                if !outputs.is_empty()
+                {
+                    self.starving[i] = 0;
+                }
+
+                for message in self.route(i, outputs)
+                {
+                    if self.deliver(message, monitor) == Control::Stop
+                    {
+                        return Outcome::Stopped;
+                    }
+                }
+                if self.machines[i].error().is_some()
+                {
+                    return Outcome::Failed(i);
+                }
+            }
+
+            if self.machines.iter().all(|m| m.halted())
+            {
+                return Outcome::Halted;
+            }
+
+            if self.queues.iter().any(|q| !q.is_empty())
+            {
+                continue;
+            }
+
+            let outcome = match self.routing
+            {
+                Routing::Links(_) =>
+                {
+                    if (0..self.machines.len())
+                        .any(|i| !self.machines[i].halted() && !self.waiting[i])
+                    {
+                        continue;
+                    }
+                    Outcome::Deadlocked
+                },
+                Routing::Packets { .. } =>
+                {
+                    // Everyone has to have asked in vain at least
+                    // twice, to be sure nothing is in flight.
+                    if (0..self.machines.len())
+                        .any(|i| !self.machines[i].halted() && self.starving[i] < 2)
+                    {
+                        continue;
+                    }
+                    Outcome::Idle
+                },
+            };
+
+            let injected = monitor.idle();
+            if injected.is_empty()
+            {
+                return outcome;
+            }
+
+            for message in injected
+            {
+                if self.deliver(message, monitor) == Control::Stop
+                {
+                    return Outcome::Stopped;
+                }
+            }
+        }
+    }
+
+    fn idleInput(&self) -> Option<ValueType>
+    {
+        match self.routing
+        {
+            Routing::Links(_) => None,
+            Routing::Packets { idle_input, .. } => Some(idle_input),
+        }
+    }
+
+    fn runMachine(&mut self, i: usize)
+    {
+        while let Some(x) = self.queues[i].pop_front()
+        {
+            self.machines[i].pushInput(x);
+            self.starving[i] = 0;
+        }
+
+        let state = match self.schedule
+        {
+            Schedule::RoundRobin(steps) => self.machines[i].runFor(steps),
+            Schedule::EventDriven => self.machines[i].run(),
+        };
+        self.waiting[i] = state == State::WaitingInput;
+
+        if self.waiting[i]
+        {
+            if let Some(x) = self.idleInput()
+            {
+                self.machines[i].pushInput(x);
+                self.starving[i] += 1;
+            }
+        }
+    }
+
+    fn route(&mut self, from: usize, outputs: Vec<ValueType>) -> Vec<Message>
+    {
+        match &self.routing
+        {
+            Routing::Links(links) =>
+            {
+                let to = match links[from]
+                {
+                    Some(to) => to as ValueType,
+                    None => self.machines.len() as ValueType,
+                };
+                outputs.into_iter().map(|x| Message { from, to, payload: vec![x] })
+                    .collect()
+            },
+            Routing::Packets { size, .. } =>
+            {
+                let size = *size;
+                self.partial[from].extend(outputs);
+                let whole = self.partial[from].len() / size * size;
+                let values: Vec<ValueType> = self.partial[from].drain(..whole).collect();
+                values.chunks(size).map(
+                    |packet| Message { from, to: packet[0], payload: packet[1..].to_vec() })
+                    .collect()
+            },
+        }
+    }
+
+    fn deliver(&mut self, message: Message, monitor: &mut dyn Monitor) -> Control
+    {
+        let control = monitor.message(&message);
+        if message.to >= 0 && (message.to as usize) < self.machines.len()
+        {
+            let to = message.to as usize;
+            self.queues[to].extend(message.payload.iter());
+        }
+        else
+        {
+            self.outbox.push(message);
+        }
+        control
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testChain()
+{
+    // Day 7 amplifiers, phases 4,3,2,1,0.
+    let code = vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+    let mut network = Network::chain(&code, 5);
+    for (i, phase) in [4, 3, 2, 1, 0].iter().enumerate()
+    {
+        network.send(i, *phase);
+    }
+    network.send(0, 0);
+    network.setSchedule(Schedule::RoundRobin(3));
+    assert_eq!(network.run(), Outcome::Halted);
+    assert_eq!(network.outbox(), &vec![Message { from: 4, to: 5, payload: vec![43210] }]);
+}
+
+#[test]
+fn testRing()
+{
+    // Day 7 feedback loop, phases 9,8,7,6,5.
+    let code = vec![3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,
+                    27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5];
+    for schedule in &[Schedule::EventDriven, Schedule::RoundRobin(1)]
+    {
+        let mut network = Network::ring(&code, 5);
+        network.setSchedule(*schedule);
+        for (i, phase) in [9, 8, 7, 6, 5].iter().enumerate()
+        {
+            network.send(i, *phase);
+        }
+        network.send(0, 0);
+
+        let mut last: ValueType = 0;
+        let outcome = network.runWithMonitor(&mut |message: &Message|
+        {
+            if message.from == 4
+            {
+                last = message.payload[0];
+            }
+            Control::Continue
+        });
+        assert_eq!(outcome, Outcome::Halted);
+        assert_eq!(last, 139629729);
+    }
+}
+
+#[test]
+fn testDeadlock()
+{
+    // Both machines want an input before sending anything.
+    let code = vec![3,10,4,10,1105,1,0];
+    let mut network = Network::ring(&code, 2);
+    assert_eq!(network.run(), Outcome::Deadlocked);
+
+    // The monitor gets the loop going, then stops it.
+    let mut network = Network::ring(&code, 2);
+    struct Kick { seen: usize }
+    impl Monitor for Kick
+    {
+        fn message(&mut self, _: &Message) -> Control
+        {
+            self.seen += 1;
+            if self.seen == 10 { Control::Stop } else { Control::Continue }
+        }
+
+        fn idle(&mut self) -> Vec<Message>
+        {
+            vec![Message { from: 0, to: 0, payload: vec![1] }]
+        }
+    }
+    let mut kick = Kick { seen: 0 };
+    assert_eq!(network.runWithMonitor(&mut kick), Outcome::Stopped);
+    assert_eq!(kick.seen, 10);
+}
+
+#[test]
+fn testPackets()
+{
+    // Read the address, then send one packet [address + 1, address,
+    // 0] to the next machine, and then print out everything
+    // received, ignoring idle inputs.
+    //
+    //     input addr
+    //     add addr, 1, next
+    //     eq next, 3, wrap
+    //     jmpf wrap, :send
+    //     add 0, 0, next
+    // send:
+    //     output next
+    //     output addr
+    //     output 0
+    // receive:
+    //     input x
+    //     eq x, -1, nothing
+    //     jmpt nothing, :receive
+    //     output 255
+    //     output x
+    //     output addr
+    //     jmpt 1, :receive
+    let code = vec![3,52,1001,52,1,53,1008,53,3,54,1006,54,17,1101,0,0,53,4,53,4,52,
+                    104,0,3,55,1008,55,-1,56,1005,56,23,104,255,4,55,4,52,1105,1,23];
+
+    let mut network = Network::packets(&code, 3, 3, -1);
+    let mut nat: Vec<Message> = vec![];
+    let outcome = network.runWithMonitor(&mut |message: &Message|
+    {
+        if message.to == 255
+        {
+            nat.push(message.clone());
+        }
+        Control::Continue
+    });
+    assert_eq!(outcome, Outcome::Idle);
+    assert_eq!(nat.len(), 6);
+    assert_eq!(network.outbox(), &nat);
+    // Machine 1 received address 0 then 0 from machine 0.
+    assert!(nat.contains(&Message { from: 1, to: 255, payload: vec![0, 1] }));
+    assert!(nat.contains(&Message { from: 0, to: 255, payload: vec![2, 0] }));
+}
+
+#[test]
+fn testFailed()
+{
+    // The second machine gets an invalid opcode as soon as it reads
+    // its input.
+    let mut machines = vec![IntCodeComputer::new(), IntCodeComputer::new()];
+    machines[0].loadCode(&[104,42,99]);
+    machines[1].loadCode(&[3,0,1105,1,0]);
+    let mut network = Network::new(machines, Routing::Links(vec![Some(1), None]));
+    assert_eq!(network.run(), Outcome::Failed(1));
+    assert_eq!(network.machines[1].error().unwrap(), "Invalid opcode 42");
+}
+
+#[test]
+#[should_panic(expected = "Packets need at least the address")]
+fn testEmptyPackets()
+{
+    Network::packets(&[99], 1, 0, -1);
+}
@@ -0,0 +1,325 @@
+#![allow(dead_code)]
+
+use std::vec::Vec;
+use std::ops::Range;
+use std::convert::TryFrom;
+use std::thread;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{IntCodeComputer, ValueType};
+
+// Something the search can change about a run.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Slot
+{
+    // The memory cell at this address, patched before running.
+    Memory(usize),
+    // The input with this index.
+    Input(usize),
+}
+
+// Brute-force search over memory patches and inputs of a program,
+// like the noun and verb of day 2.
+pub struct Search
+{
+    code: Vec<ValueType>,
+    slots: Vec<(Slot, Range<ValueType>)>,
+    // Inputs that are not searched over.
+    inputs: Vec<ValueType>,
+    entry: usize,
+    distinct: bool,
+    threads: usize,
+    max_steps: usize,
+}
+
+// A matching assignment. The values are in the order the slots were
+// added.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Found
+{
+    pub slots: Vec<Slot>,
+    pub values: Vec<ValueType>,
+}
+
+impl Search
+{
+    pub fn new(code: &[ValueType]) -> Self
+    {
+        Search
+        {
+            code: code.to_vec(),
+            slots: vec![],
+            inputs: vec![],
+            entry: 0,
+            distinct: false,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            max_steps: 10_000_000,
+        }
+    }
+
+    // Try every value in `values' for memory at `address'.
+    pub fn addMemory(&mut self, address: usize, values: Range<ValueType>)
+    {
+        self.slots.push((Slot::Memory(address), values));
+    }
+
+    // Try every value in `values' for input number `index'.
+    pub fn addInput(&mut self, index: usize, values: Range<ValueType>)
+    {
+        self.slots.push((Slot::Input(index), values));
+    }
+
+    // Inputs for the indices that are not searched over. Missing ones
+    // are 0.
+    pub fn setInputs(&mut self, inputs: &[ValueType])
+    {
+        self.inputs = inputs.to_vec();
+    }
+
+    // Start the program at `address' instead of 0.
+    pub fn setEntry(&mut self, address: usize)
+    {
+        self.entry = address;
+    }
+
+    // Only try assignments where all values are different, like
+    // permutations of phases in day 7.
+    pub fn setDistinct(&mut self, distinct: bool)
+    {
+        self.distinct = distinct;
+    }
+
+    pub fn setThreads(&mut self, threads: usize)
+    {
+        self.threads = threads.max(1);
+    }
+
+    // Give up on a run after this many instructions, so that
+    // assignments that put the program into an infinite loop do not
+    // hang the search.
+    pub fn setMaxSteps(&mut self, steps: usize)
+    {
+        self.max_steps = steps;
+    }
+
+    // Number of assignments, if it fits.
+    pub fn size(&self) -> Option<usize>
+    {
+        self.slots.iter().try_fold(1usize, |size, (_, values)|
+        {
+            let count = usize::try_from(values.end.saturating_sub(values.start).max(0)).ok()?;
+            size.checked_mul(count)
+        })
+    }
+
+    // The assignment with the given index, counting in mixed radix
+    // with the last slot changing fastest.
+    fn assignment(&self, mut index: usize) -> Vec<ValueType>
+    {
+        let mut values: Vec<ValueType> = vec![0; self.slots.len()];
+        for (i, (_, range)) in self.slots.iter().enumerate().rev()
+        {
+            let count = (range.end - range.start) as usize;
+            values[i] = range.start + (index % count) as ValueType;
+            index /= count;
+        }
+        values
+    }
+
+    // Make sure that every memory slot is in the memory of the
+    // computer.
+    fn checkSlots(&self, computer: &IntCodeComputer) -> Result<(), String>
+    {
+        for (slot, _) in &self.slots
+        {
+            if let Slot::Memory(address) = slot
+            {
+                if *address >= computer.mem.len()
+                {
+                    return Err(format!("Cannot vary {}, memory size is {}",
+                                       address, computer.mem.len()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Run the program with an assignment, and hand back the computer.
+    pub fn run(&self, values: &[ValueType]) -> Result<IntCodeComputer, String>
+    {
+        let mut computer = IntCodeComputer::new();
+        self.checkSlots(&computer)?;
+        computer.loadCode(&self.code);
+        computer.setCursor(self.entry);
+        let mut inputs = self.inputs.clone();
+
+        for ((slot, _), value) in self.slots.iter().zip(values)
+        {
+            match slot
+            {
+                Slot::Memory(address) => { computer.mem[*address] = *value; },
+                Slot::Input(index) =>
+                {
+                    if inputs.len() <= *index
+                    {
+                        inputs.resize(*index + 1, 0);
+                    }
+                    inputs[*index] = *value;
+                },
+            }
+        }
+
+        for x in inputs
+        {
+            computer.pushInput(x);
+        }
+        computer.runFor(self.max_steps);
+        Ok(computer)
+    }
+
+    // Find the first assignment for which `predicate' is true on the
+    // computer after the run. The assignments are tried in parallel,
+    // but the result is always the first match in order. A run that
+    // stops with an error, such as an access outside of the computer
+    // or an invalid opcode, is not a match.
+    pub fn find<P>(&self, predicate: P) -> Result<Option<Found>, String>
+    where P: Fn(&IntCodeComputer) -> bool + Sync
+    {
+        let size = self.size().ok_or(String::from("Too many assignments to search"))?;
+        self.checkSlots(&IntCodeComputer::new())?;
+        let best = AtomicUsize::new(usize::MAX);
+
+        thread::scope(
+            |scope|
+            {
+                for start in 0..self.threads
+                {
+                    let best = &best;
+                    let predicate = &predicate;
+                    scope.spawn(
+                        move ||
+                        {
+                            let mut index = start;
+                            while index < size && index < best.load(Ordering::Relaxed)
+                            {
+                                let values = self.assignment(index);
+                                let matches = |computer: IntCodeComputer|
+                                    computer.error().is_none() && predicate(&computer);
+                                if (!self.distinct || isDistinct(&values))
+                                    && self.run(&values).is_ok_and(matches)
+                                {
+                                    best.fetch_min(index, Ordering::Relaxed);
+                                    return;
+                                }
+                                index += self.threads;
+                            }
+                        });
+                }
+            });
+
+        let index = best.load(Ordering::Relaxed);
+        if index == usize::MAX
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(Found
+        {
+            slots: self.slots.iter().map(|(slot, _)| *slot).collect(),
+            values: self.assignment(index),
+        }))
+    }
+}
+
+fn isDistinct(values: &[ValueType]) -> bool
+{
+    (0..values.len()).all(|i| !values[i + 1..].contains(&values[i]))
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testMemory()
+{
+    // mem[0] = noun + verb, in immediate mode.
+    let code = vec![1101,0,0,0,99];
+    let mut search = Search::new(&code);
+    search.addMemory(1, 0..5);
+    search.addMemory(2, 0..5);
+    for threads in 1..4
+    {
+        search.setThreads(threads);
+        let found = search.find(|computer| computer.mem[0] == 5).unwrap().unwrap();
+        assert_eq!(found.slots, vec![Slot::Memory(1), Slot::Memory(2)]);
+        assert_eq!(found.values, vec![1, 4]);
+    }
+    assert_eq!(search.find(|computer| computer.mem[0] == 9).unwrap(), None);
+}
+
+#[test]
+fn testInput()
+{
+    // Output the product of two inputs.
+    let code = vec![3,11,3,12,2,11,12,13,4,13,99,0,0,0];
+    let mut search = Search::new(&code);
+    search.addInput(0, 0..10);
+    search.addInput(1, 0..10);
+    search.setThreads(2);
+    let found = search.find(|computer| computer.output.last() == Some(&12)).unwrap().unwrap();
+    assert_eq!(found.values, vec![2, 6]);
+
+    search.setDistinct(true);
+    let found = search.find(|computer| computer.output.last() == Some(&25)).unwrap();
+    assert_eq!(found, None);
+
+    // The second input is fixed.
+    let mut search = Search::new(&code);
+    search.setInputs(&[0, 7]);
+    search.addInput(0, 0..10);
+    let found = search.find(|computer| computer.output.last() == Some(&21)).unwrap().unwrap();
+    assert_eq!(found.values, vec![3]);
+}
+
+#[test]
+fn testMaxSteps()
+{
+    // Loop forever unless the input is 0.
+    let code = vec![3,7,1005,7,2,99,0,0];
+    let mut search = Search::new(&code);
+    search.addInput(0, -3..3);
+    search.setMaxSteps(100);
+    let found = search.find(|computer| computer.halted()).unwrap().unwrap();
+    assert_eq!(found.values, vec![0]);
+}
+
+#[test]
+fn testFault()
+{
+    // Read from the address in the input and halt. Negative ones are
+    // outside of memory.
+    let code = vec![3,3,4,0,99];
+    let mut search = Search::new(&code);
+    search.addInput(0, -5..5);
+    search.setThreads(3);
+    let found = search.find(|computer| computer.error().is_some() || computer.halted())
+        .unwrap().unwrap();
+    assert_eq!(found.values, vec![0]);
+
+    let mut search = Search::new(&code);
+    search.addMemory(100000, 0..2);
+    assert_eq!(search.find(|_| true), Err(String::from("Cannot vary 100000, memory size is 8192")));
+    assert!(search.run(&[0]).is_err());
+}
+
+#[test]
+fn testError()
+{
+    // Run the input as an opcode. Everything but 99 stops with an
+    // error, which also halts the computer.
+    let code = vec![3,2,0];
+    let mut search = Search::new(&code);
+    search.addInput(0, 0..100);
+    search.setThreads(4);
+    let found = search.find(|computer| computer.halted()).unwrap().unwrap();
+    assert_eq!(found.values, vec![99]);
+}
@@ -0,0 +1,150 @@
+#![allow(dead_code)]
+
+use std::vec::Vec;
+use std::thread::{self, JoinHandle};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use super::{IntCodeComputer, State, ValueType};
+
+// Run `computer' on its own thread. When it needs input, it waits on
+// `input'; its outputs are sent to `output' as soon as they are
+// produced. The thread ends when the computer halts, or when it needs
+// input and `input' is closed. Either way the computer is handed back
+// through the join handle, so its memory can be inspected.
+pub fn spawn(mut computer: IntCodeComputer, input: Receiver<ValueType>,
+             output: Sender<ValueType>) -> JoinHandle<IntCodeComputer>
+{
+    thread::spawn(
+        move ||
+        {
+            loop
+            {
+                let state = computer.run();
+                for x in computer.takeOutput()
+                {
+                    // Nobody listening is not our problem.
+                    let _ = output.send(x);
+                }
+
+                match state
+                {
+                    State::WaitingInput =>
+                    {
+                        match input.recv()
+                        {
+                            Ok(x) => computer.pushInput(x),
+                            Err(_) => { break; },
+                        }
+                    },
+                    _ => { break; },
+                }
+            }
+            computer
+        })
+}
+
+// Computers running in parallel, each on its own thread, with the
+// output of one connected to the input of the next.
+pub struct Pipeline
+{
+    // Input of the first computer.
+    pub input: Sender<ValueType>,
+    // Output of the last computer. Closes when it stops.
+    pub output: Receiver<ValueType>,
+    handles: Vec<JoinHandle<IntCodeComputer>>,
+}
+
+impl Pipeline
+{
+    pub fn chain(computers: Vec<IntCodeComputer>) -> Self
+    {
+        let (input, mut receiver) = mpsc::channel();
+        let mut handles: Vec<JoinHandle<IntCodeComputer>> = vec![];
+        for computer in computers
+        {
+            let (sender, next_receiver) = mpsc::channel();
+            handles.push(spawn(computer, receiver, sender));
+            receiver = next_receiver;
+        }
+
+        Pipeline
+        {
+            input,
+            output: receiver,
+            handles,
+        }
+    }
+
+    // Wait for all computers to stop, and get them back in order.
+    pub fn join(self) -> Vec<IntCodeComputer>
+    {
+        drop(self.input);
+        self.handles.into_iter().map(|h| h.join().expect("Computer thread panicked"))
+            .collect()
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testSend()
+{
+    fn assertSend<T: Send>() {}
+    assertSend::<IntCodeComputer>();
+}
+
+fn amplifiers(code: &[ValueType], phases: &[ValueType]) -> Vec<IntCodeComputer>
+{
+    phases.iter().map(
+        |phase|
+        {
+            let mut computer = IntCodeComputer::new();
+            computer.loadCode(code);
+            computer.pushInput(*phase);
+            computer
+        }).collect()
+}
+
+#[test]
+fn testChain()
+{
+    // Day 7 amplifiers, phases 4,3,2,1,0.
+    let code = vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+    let pipeline = Pipeline::chain(amplifiers(&code, &[4, 3, 2, 1, 0]));
+    pipeline.input.send(0).unwrap();
+    let output: Vec<ValueType> = pipeline.output.iter().collect();
+    assert_eq!(output, vec![43210]);
+    assert!(pipeline.join().iter().all(|c| c.halted()));
+}
+
+#[test]
+fn testFeedback()
+{
+    // Day 7 feedback loop, phases 9,8,7,6,5. Close the loop by hand.
+    let code = vec![3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,
+                    27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5];
+    let pipeline = Pipeline::chain(amplifiers(&code, &[9, 8, 7, 6, 5]));
+    pipeline.input.send(0).unwrap();
+    let mut last: ValueType = 0;
+    for x in pipeline.output.iter()
+    {
+        last = x;
+        // The first one may have halted already.
+        let _ = pipeline.input.send(x);
+    }
+    assert_eq!(last, 139629729);
+    pipeline.join();
+}
+
+#[test]
+fn testClosedInput()
+{
+    let (sender, receiver) = mpsc::channel();
+    let (output, _) = mpsc::channel();
+    let mut computer = IntCodeComputer::new();
+    computer.loadCode(&vec![3,0,99]);
+    let handle = spawn(computer, receiver, output);
+    drop(sender);
+    let computer = handle.join().unwrap();
+    assert!(!computer.halted());
+}
@@ -0,0 +1,21 @@
+#![allow(non_snake_case)]
+
+// The reusable parts of the project: the Intcode VM with its assembler
+// and tools, and the math and grid helpers used by the puzzles. The
+// binaries (the puzzle runner, emulator, assembler and analyzer) are
+// built on top of this crate.
+
+pub mod lib
+{
+    pub mod vector;
+    pub mod ratio;
+    pub mod grid;
+    pub mod meta;
+    pub mod stats;
+    pub mod vis;
+    pub mod svg;
+    pub mod gif;
+    pub mod term;
+}
+
+pub mod intcode;
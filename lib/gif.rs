@@ -0,0 +1,362 @@
+#![allow(dead_code)]
+
+// A small GIF encoder for animations. Frames are written one at a
+// time as they come, so a long animation does not have to be kept in
+// memory. Each frame gets its own color table, which is fine for the
+// few colors our pictures use, and only the part that changed since
+// the frame before is written.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::vec::Vec;
+
+use super::svg::{Color, Raster};
+
+// Largest LZW code width that GIF allows.
+const MAX_CODE_BITS: u32 = 12;
+
+pub struct Encoder<W: Write>
+{
+    out: W,
+    width: u16,
+    height: u16,
+    // The frame before, as it shows on the screen.
+    previous: Option<Raster>,
+}
+
+impl<W: Write> Encoder<W>
+{
+    // Start an animation of `width' by `height' pixels that loops
+    // forever.
+    pub fn new(mut out: W, width: u16, height: u16) -> io::Result<Self>
+    {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // No global color table, background 0, square pixels.
+        out.write_all(&[0, 0, 0])?;
+        // Loop forever.
+        out.write_all(&[0x21, 0xff, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+        Ok(Encoder { out, width, height, previous: None })
+    }
+
+    // Add `raster' as a frame shown for `delay_ms'. Pixels outside the
+    // animation are cut off, and missing ones are black. A frame can
+    // have at most 256 colors.
+    pub fn frame(&mut self, raster: &Raster, delay_ms: u32) -> io::Result<()>
+    {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut screen = raster.clone();
+        if (raster.width, raster.height) != (width, height)
+        {
+            screen = Raster { width, height, pixels: vec![(0, 0, 0); width * height] };
+            for y in 0..height.min(raster.height)
+            {
+                for x in 0..width.min(raster.width)
+                {
+                    screen.pixels[y * width + x] = raster.pixels[y * raster.width + x];
+                }
+            }
+        }
+
+        // The smallest rectangle with all the changes in it, but at
+        // least a pixel, so that the frame still takes its time.
+        let (mut left, mut top, mut right, mut bottom) = (0, 0, width.max(1), height.max(1));
+        if let Some(previous) = &self.previous
+        {
+            let (mut min, mut max) = (width, 0);
+            let rows = previous.pixels.chunks(width.max(1)).zip(screen.pixels.chunks(width.max(1)));
+            let changed: Vec<usize> = rows.enumerate().filter(|(_, (a, b))| a != b).map(
+                |(y, (a, b))|
+                {
+                    min = min.min(a.iter().zip(b.iter()).position(|(p, q)| p != q).unwrap());
+                    max = max.max(width - a.iter().rev().zip(b.iter().rev()).position(|(p, q)| p != q).unwrap());
+                    y
+                }).collect();
+            if let (Some(first), Some(last)) = (changed.first(), changed.last())
+            {
+                left = min;
+                right = max;
+                top = *first;
+                bottom = last + 1;
+            }
+            else
+            {
+                right = 1;
+                bottom = 1;
+            }
+        }
+        right = right.min(width).max(left + 1);
+        bottom = bottom.min(height).max(top + 1);
+
+        let mut palette: Vec<Color> = vec![];
+        let mut lookup: HashMap<Color, u8> = HashMap::new();
+        let mut indices: Vec<u8> = Vec::with_capacity((right - left) * (bottom - top));
+        let mut last: (Color, u8) = ((0, 0, 0), 0);
+        for y in top..bottom
+        {
+            for x in left..right
+            {
+                let color = screen.pixels[y * width + x];
+                // Colors come in runs, so try the one before first.
+                if color == last.0 && !indices.is_empty()
+                {
+                    indices.push(last.1);
+                    continue;
+                }
+                let index = match lookup.get(&color)
+                {
+                    Some(index) => *index,
+                    None =>
+                    {
+                        if palette.len() == 256
+                        {
+                            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                                      "Too many colors in a GIF frame"));
+                        }
+                        palette.push(color);
+                        lookup.insert(color, (palette.len() - 1) as u8);
+                        (palette.len() - 1) as u8
+                    },
+                };
+                indices.push(index);
+                last = (color, index);
+            }
+        }
+        self.previous = Some(screen);
+
+        // The color table has 2^(size + 1) entries, at least 2.
+        let mut size = 0;
+        while (2 << size) < palette.len()
+        {
+            size += 1;
+        }
+
+        // Graphic control extension, with the delay in centiseconds.
+        // The frame stays when the next one comes, so that one only
+        // needs the changes.
+        let delay = ((delay_ms + 5) / 10).min(u16::MAX as u32) as u16;
+        self.out.write_all(&[0x21, 0xf9, 4, 1 << 2])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+
+        // Image descriptor with a local color table.
+        self.out.write_all(&[0x2c])?;
+        for value in &[left, top, right - left, bottom - top]
+        {
+            self.out.write_all(&(*value as u16).to_le_bytes())?;
+        }
+        self.out.write_all(&[0x80 | size as u8])?;
+        for i in 0..(2 << size)
+        {
+            let (r, g, b) = palette.get(i).cloned().unwrap_or((0, 0, 0));
+            self.out.write_all(&[r, g, b])?;
+        }
+
+        let min_code_bits = (size + 1).max(2);
+        self.out.write_all(&[min_code_bits as u8])?;
+        for block in compress(&indices, min_code_bits).chunks(255)
+        {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    // Write the trailer, and hand back the output.
+    pub fn finish(mut self) -> io::Result<W>
+    {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// Packs codes of varying width into bytes, least significant bit
+// first.
+struct BitWriter
+{
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter
+{
+    fn write(&mut self, code: u16, width: u32)
+    {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8
+        {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8>
+    {
+        if self.bits > 0
+        {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// LZW-compress `indices' the way GIF wants it.
+fn compress(indices: &[u8], min_code_bits: u32) -> Vec<u8>
+{
+    let clear = 1u16 << min_code_bits;
+    let end = clear + 1;
+    let mut writer = BitWriter { bytes: vec![], buffer: 0, bits: 0 };
+    // Codes of the strings seen so far, by the code of their prefix
+    // and their last index, or 0 for none.
+    let colors = clear as usize;
+    let mut table: Vec<u16> = vec![0; colors << MAX_CODE_BITS];
+    let mut next = end + 1;
+    let mut width = min_code_bits + 1;
+
+    writer.write(clear, width);
+    let mut current: Option<u16> = None;
+    for &index in indices
+    {
+        let prefix = match current
+        {
+            Some(prefix) => prefix,
+            None => { current = Some(index as u16); continue; },
+        };
+        let entry = prefix as usize * colors + index as usize;
+        if table[entry] != 0
+        {
+            current = Some(table[entry]);
+            continue;
+        }
+
+        writer.write(prefix, width);
+        if next == 1 << MAX_CODE_BITS
+        {
+            writer.write(clear, width);
+            table.iter_mut().for_each(|code| *code = 0);
+            next = end + 1;
+            width = min_code_bits + 1;
+        }
+        else
+        {
+            table[entry] = next;
+            // The decoder widens its codes one code later than we
+            // add them.
+            if next == 1 << width
+            {
+                width += 1;
+            }
+            next += 1;
+        }
+        current = Some(index as u16);
+    }
+    if let Some(code) = current
+    {
+        writer.write(code, width);
+    }
+    writer.write(end, width);
+    writer.finish()
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testCompress()
+{
+    // A plain GIF LZW decoder, to check that what we write reads back.
+    fn decompress(bytes: &[u8], min_code_bits: u32) -> Vec<u8>
+    {
+        let clear = 1usize << min_code_bits;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut width = min_code_bits + 1;
+        let (mut buffer, mut bits, mut pos) = (0u32, 0u32, 0usize);
+        let mut previous: Option<Vec<u8>> = None;
+        let mut result = vec![];
+        loop
+        {
+            while bits < width
+            {
+                buffer |= (bytes[pos] as u32) << bits;
+                pos += 1;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << width) - 1)) as usize;
+            buffer >>= width;
+            bits -= width;
+
+            if code == clear
+            {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.push(vec![]);
+                table.push(vec![]);
+                width = min_code_bits + 1;
+                previous = None;
+                continue;
+            }
+            if code == end
+            {
+                return result;
+            }
+            let entry = match (code < table.len(), &previous)
+            {
+                (true, _) => table[code].clone(),
+                (false, Some(p)) => { let mut e = p.clone(); e.push(p[0]); e },
+                (false, None) => panic!("Bad code {}", code),
+            };
+            if let Some(mut p) = previous
+            {
+                p.push(entry[0]);
+                table.push(p);
+            }
+            if table.len() == 1 << width && width < MAX_CODE_BITS
+            {
+                width += 1;
+            }
+            result.extend(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    let short: Vec<u8> = vec![0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 0];
+    assert_eq!(decompress(&compress(&short, 2), 2), short);
+    // Long enough to fill up the table a few times.
+    let long: Vec<u8> = (0..100000u64).map(|i| ((i * i / 7 + i / 13) % 5) as u8).collect();
+    assert_eq!(decompress(&compress(&long, 3), 3), long);
+    assert_eq!(decompress(&compress(&[], 2), 2), Vec::<u8>::new());
+}
+
+#[test]
+fn testEncoder()
+{
+    let raster = Raster { width: 3, height: 2,
+                          pixels: vec![(255, 0, 0), (0, 0, 0), (255, 0, 0),
+                                       (0, 0, 0), (0, 0, 0), (0, 0, 255)] };
+    let mut changed = raster.clone();
+    changed.pixels[4] = (255, 0, 0);
+    let mut encoder = Encoder::new(vec![], 3, 2).unwrap();
+    encoder.frame(&raster, 100).unwrap();
+    encoder.frame(&raster, 40).unwrap();
+    encoder.frame(&changed, 40).unwrap();
+    let bytes = encoder.finish().unwrap();
+    assert_eq!(&bytes[..6], b"GIF89a");
+    assert_eq!(&bytes[6..10], &[3, 0, 2, 0]);
+    assert_eq!(*bytes.last().unwrap(), 0x3b);
+
+    // Each frame has its delay in centiseconds, and the rectangle that
+    // changed as left, top, width and height.
+    let frames: Vec<(u16, Vec<u16>)> = (0..bytes.len() - 17)
+        .filter(|i| bytes[*i..*i + 4] == [0x21, 0xf9, 4, 4] && bytes[*i + 8] == 0x2c)
+        .map(|i| (u16::from_le_bytes([bytes[i + 4], bytes[i + 5]]),
+                  (0..4).map(|k| u16::from_le_bytes([bytes[i + 9 + 2 * k], bytes[i + 10 + 2 * k]]))
+                  .collect()))
+        .collect();
+    assert_eq!(frames, vec![(10, vec![0, 0, 3, 2]), (4, vec![0, 0, 1, 1]), (4, vec![1, 1, 1, 1])]);
+}
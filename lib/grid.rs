@@ -0,0 +1,217 @@
+#![allow(dead_code)]
+
+// Helpers for the 2D grids that many puzzles walk around in. The y
+// axis points down, like on the screen.
+
+use std::vec::Vec;
+
+pub type Coord = (i64, i64);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction
+{
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction
+{
+    // Clockwise from up.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn turnRight(&self) -> Self
+    {
+        Self::ALL[(*self as usize + 1) % 4]
+    }
+
+    pub fn turnLeft(&self) -> Self
+    {
+        Self::ALL[(*self as usize + 3) % 4]
+    }
+
+    pub fn opposite(&self) -> Self
+    {
+        Self::ALL[(*self as usize + 2) % 4]
+    }
+
+    // The coordinate one step from `from' in this direction.
+    pub fn step(&self, from: &Coord) -> Coord
+    {
+        match self
+        {
+            Direction::Up => (from.0, from.1 - 1),
+            Direction::Right => (from.0 + 1, from.1),
+            Direction::Down => (from.0, from.1 + 1),
+            Direction::Left => (from.0 - 1, from.1),
+        }
+    }
+}
+
+// A rectangle of coordinates. Both ranges are half-open.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds
+{
+    pub x: (i64, i64),
+    pub y: (i64, i64),
+}
+
+impl Bounds
+{
+    pub fn new(x: (i64, i64), y: (i64, i64)) -> Self
+    {
+        Bounds { x, y }
+    }
+
+    // The smallest bounds containing all of `coords', or empty bounds
+    // at the origin if there is none.
+    pub fn of<'a, I>(coords: I) -> Self
+    where I: IntoIterator<Item = &'a Coord>
+    {
+        let mut result: Option<Bounds> = None;
+        for coord in coords
+        {
+            let bounds = result.get_or_insert(Bounds::new((coord.0, coord.0 + 1),
+                                                          (coord.1, coord.1 + 1)));
+            bounds.x = (bounds.x.0.min(coord.0), bounds.x.1.max(coord.0 + 1));
+            bounds.y = (bounds.y.0.min(coord.1), bounds.y.1.max(coord.1 + 1));
+        }
+        result.unwrap_or(Bounds::new((0, 0), (0, 0)))
+    }
+
+    pub fn width(&self) -> usize
+    {
+        (self.x.1 - self.x.0).max(0) as usize
+    }
+
+    pub fn height(&self) -> usize
+    {
+        (self.y.1 - self.y.0).max(0) as usize
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool
+    {
+        self.x.0 <= coord.0 && coord.0 < self.x.1 && self.y.0 <= coord.1 && coord.1 < self.y.1
+    }
+
+    // Bounds grown by `by' on every side.
+    pub fn expanded(&self, by: i64) -> Self
+    {
+        Bounds::new((self.x.0 - by, self.x.1 + by), (self.y.0 - by, self.y.1 + by))
+    }
+
+    // Row-major index of `coord'.
+    pub fn index(&self, coord: &Coord) -> Option<usize>
+    {
+        if !self.contains(coord)
+        {
+            return None;
+        }
+        Some((coord.1 - self.y.0) as usize * self.width() + (coord.0 - self.x.0) as usize)
+    }
+
+    pub fn coord(&self, index: usize) -> Coord
+    {
+        ((index % self.width()) as i64 + self.x.0, (index / self.width()) as i64 + self.y.0)
+    }
+
+    // All coordinates, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Coord>
+    {
+        let x = self.x;
+        (self.y.0..self.y.1).flat_map(move |y| (x.0..x.1).map(move |x| (x, y)))
+    }
+}
+
+// A dense grid of cells over some bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T>
+{
+    pub bounds: Bounds,
+    data: Vec<T>,
+}
+
+impl<T: Clone> Grid<T>
+{
+    pub fn new(bounds: Bounds, fill: T) -> Self
+    {
+        Grid { bounds, data: vec![fill; bounds.width() * bounds.height()] }
+    }
+
+    pub fn get(&self, coord: &Coord) -> Option<&T>
+    {
+        self.bounds.index(coord).map(|i| &self.data[i])
+    }
+
+    pub fn get_mut(&mut self, coord: &Coord) -> Option<&mut T>
+    {
+        self.bounds.index(coord).map(move |i| &mut self.data[i])
+    }
+
+    // Set the cell at `coord'. Return false if it is out of bounds.
+    pub fn set(&mut self, coord: &Coord, value: T) -> bool
+    {
+        match self.get_mut(coord)
+        {
+            Some(cell) => { *cell = value; true },
+            None => false,
+        }
+    }
+
+    // Cells with their coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)>
+    {
+        self.bounds.coords().zip(self.data.iter())
+    }
+
+    // Draw the grid as text, one line per row.
+    pub fn render<F>(&self, draw: F) -> String
+    where F: Fn(&T) -> char
+    {
+        self.data.chunks(self.bounds.width().max(1)).map(
+            |row| row.iter().map(&draw).collect::<String>())
+            .collect::<Vec<String>>().join("\n")
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testDirection()
+{
+    assert_eq!(Direction::Up.turnRight(), Direction::Right);
+    assert_eq!(Direction::Up.turnLeft(), Direction::Left);
+    assert_eq!(Direction::Left.opposite(), Direction::Right);
+    assert_eq!(Direction::Up.step(&(0, 0)), (0, -1));
+    assert_eq!(Direction::Left.step(&(0, 0)), (-1, 0));
+}
+
+#[test]
+fn testBounds()
+{
+    let bounds = Bounds::of(&vec![(-1, 2), (3, -2)]);
+    assert_eq!(bounds, Bounds::new((-1, 4), (-2, 3)));
+    assert_eq!((bounds.width(), bounds.height()), (5, 5));
+    assert!(bounds.contains(&(3, 2)));
+    assert!(!bounds.contains(&(4, 2)));
+    assert_eq!(bounds.index(&(-1, -2)), Some(0));
+    assert_eq!(bounds.index(&(0, -1)), Some(6));
+    assert_eq!(bounds.coord(6), (0, -1));
+    assert_eq!(bounds.index(&(5, 0)), None);
+    assert_eq!(bounds.coords().count(), 25);
+    assert_eq!(Bounds::of(&vec![]).width(), 0);
+}
+
+#[test]
+fn testGrid()
+{
+    let mut grid = Grid::new(Bounds::new((0, 3), (-1, 1)), false);
+    assert!(grid.set(&(1, 0), true));
+    assert!(!grid.set(&(3, 0), true));
+    assert_eq!(grid.get(&(1, 0)), Some(&true));
+    assert_eq!(grid.get(&(1, 1)), None);
+    assert_eq!(grid.iter().filter(|(_, x)| **x).map(|(c, _)| c).collect::<Vec<Coord>>(),
+               vec![(1, 0)]);
+    assert_eq!(grid.render(|x| if *x { '#' } else { '.' }), "...\n.#.");
+}
@@ -4,7 +4,7 @@ use std::fmt;
 use std::ops::{Add, Div, Rem, Mul};
 use std::iter::Sum;
 use std::cmp::Ordering;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 
 pub trait ValueTraits<T=Self>
     : Div<Output=T>
@@ -25,7 +25,7 @@ impl<T> ValueTraits<T> for T where T
 {}
 
 
-#[derive(Hash, Copy, Clone)]
+#[derive(Copy, Clone)]
 pub struct Ratio<T>
 where T: ValueTraits<T>
 {
@@ -42,9 +42,7 @@ where T: ValueTraits<T>
 
     if r1 < r2
     {
-        let temp = r1;
-        r1 = r2;
-        r2 = temp;
+        std::mem::swap(&mut r1, &mut r2);
     }
 
     let zero: T = T::from(0u8);
@@ -164,6 +162,20 @@ impl<T> Eq for Ratio<T>
 where T: ValueTraits<T>
 {}
 
+// Like `eq()', zeros are the same whatever the denominator.
+impl<T> Hash for Ratio<T>
+where T: ValueTraits<T>
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.numeritor.hash(state);
+        if !self.isZero()
+        {
+            self.denominator.hash(state);
+        }
+    }
+}
+
 impl<T> PartialOrd for Ratio<T>
 where T: ValueTraits<T>
 {
@@ -248,6 +260,10 @@ fn testRatioEq()
     assert_eq!(Ratio::from(12u32, 8u32).unwrap(), Ratio::from(3u32, 2u32).unwrap());
     assert_ne!(Ratio::from(12u32, 8u32).unwrap(), Ratio::from(2u32, 3u32).unwrap());
     assert_eq!(Ratio::from(0u32, 8u32).unwrap(), Ratio::from(0u32, 2u32).unwrap());
+
+    let zeros: std::collections::HashSet<Ratio<u32>> =
+        vec![Ratio::from(0u32, 8u32).unwrap(), Ratio::from(0u32, 2u32).unwrap()].into_iter().collect();
+    assert_eq!(zeros.len(), 1);
 }
 
 #[test]
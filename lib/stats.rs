@@ -0,0 +1,83 @@
+#![allow(dead_code)]
+
+// Summary of a bunch of measurements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats
+{
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+    pub mean: f64,
+    // Sample standard deviation. Zero with fewer than 2 samples.
+    pub stddev: f64,
+}
+
+impl Stats
+{
+    pub fn fromSamples(samples: &[f64]) -> Option<Self>
+    {
+        if samples.is_empty()
+        {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = sorted.len();
+        let median = if count % 2 == 1
+        {
+            sorted[count / 2]
+        }
+        else
+        {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+        };
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let stddev = if count < 2
+        {
+            0.0
+        }
+        else
+        {
+            (sorted.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>()
+             / (count - 1) as f64).sqrt()
+        };
+
+        Some(Stats
+        {
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            median,
+            mean,
+            stddev,
+        })
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testStats()
+{
+    assert_eq!(Stats::fromSamples(&vec![]), None);
+
+    let stats = Stats::fromSamples(&vec![4.0, 1.0, 3.0, 2.0]).unwrap();
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.min, 1.0);
+    assert_eq!(stats.max, 4.0);
+    assert_eq!(stats.median, 2.5);
+    assert_eq!(stats.mean, 2.5);
+    assert!((stats.stddev - 1.2909944).abs() < 1e-6);
+
+    let stats = Stats::fromSamples(&vec![7.0]).unwrap();
+    assert_eq!(stats.median, 7.0);
+    assert_eq!(stats.stddev, 0.0);
+
+    // NaN sorts last instead of panicking.
+    let stats = Stats::fromSamples(&vec![f64::NAN, 2.0, 1.0]).unwrap();
+    assert_eq!(stats.min, 1.0);
+    assert!(stats.max.is_nan());
+    assert_eq!(stats.median, 2.0);
+}
@@ -0,0 +1,550 @@
+#![allow(dead_code)]
+
+// A small SVG drawing library. Shapes are drawn onto a `Canvas' in
+// user coordinates, optionally in named layers, and the view box is
+// either set by hand or computed from what was drawn. A canvas can
+// also be turned into pixels, for formats like GIF.
+
+use std::fmt;
+use std::vec::Vec;
+
+// Colors used by the pictures, so that they look alike.
+pub mod palette
+{
+    pub const BLACK: &str = "black";
+    pub const WHITE: &str = "white";
+    pub const RED: &str = "#ff4757";
+    pub const GREEN: &str = "#2ed573";
+    pub const BLUE: &str = "#3742fa";
+    pub const SKY: &str = "#1e90ff";
+    pub const GREY: &str = "#a4b0be";
+    pub const DARK: &str = "#2f3542";
+}
+
+pub type Point = (f64, f64);
+pub type Color = (u8, u8, u8);
+
+// The color of `name', which is “#rrggbb” or one of the few named
+// colors the palette uses. None for “none” and anything else.
+pub fn parseColor(name: &str) -> Option<Color>
+{
+    match name
+    {
+        "black" => Some((0, 0, 0)),
+        "white" => Some((255, 255, 255)),
+        "red" => Some((255, 0, 0)),
+        "blue" => Some((0, 0, 255)),
+        "grey" | "gray" => Some((128, 128, 128)),
+        _ if name.len() == 7 && name.starts_with('#') =>
+        {
+            let channel = |i: usize| u8::from_str_radix(&name[i..i + 2], 16).ok();
+            Some((channel(1)?, channel(3)?, channel(5)?))
+        },
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Shape
+{
+    Rect { x: f64, y: f64, width: f64, height: f64 },
+    Circle { center: Point, r: f64 },
+    Line { from: Point, to: Point },
+    Polyline { points: Vec<Point> },
+    Text { at: Point, content: String, size: f64 },
+}
+
+// Pixels, row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster
+{
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+// How a shape is filled and stroked. Either can be absent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style
+{
+    pub fill: Option<String>,
+    pub stroke: Option<String>,
+    pub stroke_width: f64,
+}
+
+impl Style
+{
+    pub fn fill(color: &str) -> Self
+    {
+        Style { fill: Some(String::from(color)), stroke: None, stroke_width: 0.0 }
+    }
+
+    pub fn stroke(color: &str, width: f64) -> Self
+    {
+        Style { fill: None, stroke: Some(String::from(color)), stroke_width: width }
+    }
+
+    pub fn withStroke(mut self, color: &str, width: f64) -> Self
+    {
+        self.stroke = Some(String::from(color));
+        self.stroke_width = width;
+        self
+    }
+}
+
+impl fmt::Display for Style
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, r#"fill="{}""#, self.fill.as_ref().map(|x| &x[..]).unwrap_or("none"))?;
+        match &self.stroke
+        {
+            Some(stroke) => write!(f, r#" stroke="{}" stroke-width="{}""#, stroke, self.stroke_width),
+            None => write!(f, r#" stroke="none""#),
+        }
+    }
+}
+
+pub struct Canvas
+{
+    view_box: Option<(f64, f64, f64, f64)>,
+    // Size of the picture per unit of the view box. Without it the
+    // picture has no size, and fills whatever shows it.
+    scale: Option<f64>,
+    // Space around the drawing, when the view box is computed.
+    padding: f64,
+    // Smallest and largest coordinates drawn so far.
+    extent: Option<(Point, Point)>,
+    // Name and shapes of each layer, bottom first.
+    layers: Vec<(String, Vec<(Shape, Style)>)>,
+    current: usize,
+    font: String,
+}
+
+impl Canvas
+{
+    pub fn new() -> Self
+    {
+        Canvas
+        {
+            view_box: None,
+            scale: None,
+            padding: 0.0,
+            extent: None,
+            layers: vec![(String::new(), vec![])],
+            current: 0,
+            font: String::from("IBM Plex Mono"),
+        }
+    }
+
+    pub fn setViewBox(&mut self, x: f64, y: f64, width: f64, height: f64)
+    {
+        self.view_box = Some((x, y, width, height));
+    }
+
+    pub fn setScale(&mut self, scale: f64)
+    {
+        self.scale = Some(scale);
+    }
+
+    pub fn scale(&self) -> Option<f64>
+    {
+        self.scale
+    }
+
+    pub fn setPadding(&mut self, padding: f64)
+    {
+        self.padding = padding;
+    }
+
+    pub fn setFont(&mut self, family: &str)
+    {
+        self.font = String::from(family);
+    }
+
+    // Draw on the layer called `name' from now on. A new layer goes on
+    // top of the others.
+    pub fn layer(&mut self, name: &str)
+    {
+        self.current = match self.layers.iter().position(|(layer, _)| layer == name)
+        {
+            Some(i) => i,
+            None =>
+            {
+                self.layers.push((String::from(name), vec![]));
+                self.layers.len() - 1
+            },
+        };
+    }
+
+    fn include(&mut self, point: Point)
+    {
+        let (min, max) = self.extent.get_or_insert((point, point));
+        *min = (min.0.min(point.0), min.1.min(point.1));
+        *max = (max.0.max(point.0), max.1.max(point.1));
+    }
+
+    fn push(&mut self, shape: Shape, style: &Style)
+    {
+        self.layers[self.current].1.push((shape, style.clone()));
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, style: &Style)
+    {
+        self.include((x, y));
+        self.include((x + width, y + height));
+        self.push(Shape::Rect { x, y, width, height }, style);
+    }
+
+    // A square of `size' for the grid cell at `x', `y'.
+    pub fn cell(&mut self, x: i64, y: i64, size: f64, style: &Style)
+    {
+        self.rect(x as f64 * size, y as f64 * size, size, size, style);
+    }
+
+    pub fn circle(&mut self, center: Point, r: f64, style: &Style)
+    {
+        self.include((center.0 - r, center.1 - r));
+        self.include((center.0 + r, center.1 + r));
+        self.push(Shape::Circle { center, r }, style);
+    }
+
+    pub fn line(&mut self, from: Point, to: Point, style: &Style)
+    {
+        self.include(from);
+        self.include(to);
+        self.push(Shape::Line { from, to }, style);
+    }
+
+    pub fn polyline(&mut self, points: &[Point], style: &Style)
+    {
+        for point in points
+        {
+            self.include(*point);
+        }
+        self.push(Shape::Polyline { points: points.to_vec() }, style);
+    }
+
+    // Bold text of `size', centered at `at'.
+    pub fn text(&mut self, at: Point, content: &str, size: f64, style: &Style)
+    {
+        self.include(at);
+        self.push(Shape::Text { at, content: String::from(content), size }, style);
+    }
+
+    // The lines of a grid of `columns' by `rows' cells of `size', with
+    // the top left corner at `at'.
+    pub fn grid(&mut self, at: Point, columns: usize, rows: usize, size: f64, style: &Style)
+    {
+        let (width, height) = (columns as f64 * size, rows as f64 * size);
+        for row in 0..=rows
+        {
+            let y = at.1 + row as f64 * size;
+            self.line((at.0, y), (at.0 + width, y), style);
+        }
+        for column in 0..=columns
+        {
+            let x = at.0 + column as f64 * size;
+            self.line((x, at.1), (x, at.1 + height), style);
+        }
+    }
+
+    // The view box as x, y, width and height. Unless it was set, it
+    // fits everything drawn, with the padding around.
+    pub fn viewBox(&self) -> (f64, f64, f64, f64)
+    {
+        if let Some(view_box) = self.view_box
+        {
+            return view_box;
+        }
+        match self.extent
+        {
+            Some((min, max)) => (min.0 - self.padding, min.1 - self.padding,
+                                 max.0 - min.0 + 2.0 * self.padding,
+                                 max.1 - min.1 + 2.0 * self.padding),
+            None => (0.0, 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl Default for Canvas
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Canvas
+{
+    fn shapeToSvg(&self, shape: &Shape, style: &Style) -> String
+    {
+        match shape
+        {
+            Shape::Rect { x, y, width, height } =>
+                format!(r#"<rect x="{}" y="{}" width="{}" height="{}" {} />"#, x, y, width, height, style),
+            Shape::Circle { center, r } =>
+                format!(r#"<circle cx="{}" cy="{}" r="{}" {} />"#, center.0, center.1, r, style),
+            Shape::Line { from, to } =>
+                format!(r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} />"#, from.0, from.1, to.0, to.1, style),
+            Shape::Polyline { points } =>
+            {
+                let points: Vec<String> = points.iter().map(|p| format!("{},{}", p.0, p.1)).collect();
+                format!(r#"<polyline points="{}" {} />"#, points.join(" "), style)
+            },
+            Shape::Text { at, content, size } =>
+                format!(r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle" font-family="{}" font-weight="bold" font-size="{}" {}>{}</text>"#,
+                        at.0, at.1, self.font, size, style, escape(content)),
+        }
+    }
+
+    // Everything drawn, without the <svg> element around it.
+    pub fn body(&self) -> String
+    {
+        let mut body = String::new();
+        for (name, shapes) in &self.layers
+        {
+            if shapes.is_empty()
+            {
+                continue;
+            }
+            if !name.is_empty()
+            {
+                body += &format!("<g id=\"{}\">\n", escape(name));
+            }
+            for (shape, style) in shapes
+            {
+                body += &self.shapeToSvg(shape, style);
+                body.push('\n');
+            }
+            if !name.is_empty()
+            {
+                body += "</g>\n";
+            }
+        }
+        body
+    }
+
+    // Every shape as SVG, with the box it covers, bottom first and
+    // without the layers. Text could be anywhere.
+    pub fn elements(&self) -> Vec<(String, (Point, Point))>
+    {
+        let everywhere = ((f64::NEG_INFINITY, f64::NEG_INFINITY), (f64::INFINITY, f64::INFINITY));
+        self.layers.iter().flat_map(|(_, shapes)| shapes).map(
+            |(shape, style)|
+            {
+                let half = if style.stroke.is_some() { style.stroke_width / 2.0 } else { 0.0 };
+                let around = |points: &[Point]| -> (Point, Point)
+                {
+                    let min = points.iter().fold((f64::INFINITY, f64::INFINITY),
+                                                 |m, p| (m.0.min(p.0), m.1.min(p.1)));
+                    let max = points.iter().fold((f64::NEG_INFINITY, f64::NEG_INFINITY),
+                                                 |m, p| (m.0.max(p.0), m.1.max(p.1)));
+                    ((min.0 - half, min.1 - half), (max.0 + half, max.1 + half))
+                };
+                let bounds = match shape
+                {
+                    Shape::Rect { x, y, width, height } => around(&[(*x, *y), (x + width, y + height)]),
+                    Shape::Circle { center, r } =>
+                        around(&[(center.0 - r, center.1 - r), (center.0 + r, center.1 + r)]),
+                    Shape::Line { from, to } => around(&[*from, *to]),
+                    Shape::Polyline { points } => around(points),
+                    Shape::Text { .. } => everywhere,
+                };
+                (self.shapeToSvg(shape, style), bounds)
+            }).collect()
+    }
+
+    // Draw the canvas as pixels of `scale' per unit, onto `background'.
+    // Text is left out.
+    pub fn rasterize(&self, scale: f64, background: Color) -> Raster
+    {
+        let (x0, y0, width, height) = self.viewBox();
+        let mut raster = Raster
+        {
+            width: (width * scale).round().max(1.0) as usize,
+            height: (height * scale).round().max(1.0) as usize,
+            pixels: vec![],
+        };
+        raster.pixels = vec![background; raster.width * raster.height];
+
+        // Paint the pixels whose centers are in the box from `min' to
+        // `max' and for which `inside' holds, in user coordinates.
+        let mut paint = |min: Point, max: Point, color: Color, inside: &dyn Fn(Point) -> bool|
+        {
+            let column = |x: f64| ((x - x0) * scale).floor().max(0.0) as usize;
+            let row = |y: f64| ((y - y0) * scale).floor().max(0.0) as usize;
+            for py in row(min.1)..(row(max.1) + 1).min(raster.height)
+            {
+                for px in column(min.0)..(column(max.0) + 1).min(raster.width)
+                {
+                    let center = (x0 + (px as f64 + 0.5) / scale, y0 + (py as f64 + 0.5) / scale);
+                    if inside(center)
+                    {
+                        raster.pixels[py * raster.width + px] = color;
+                    }
+                }
+            }
+        };
+
+        // Lines are at least a pixel wide.
+        let min_width = 1.0 / scale;
+        for (shape, style) in self.layers.iter().flat_map(|(_, shapes)| shapes)
+        {
+            let fill = style.fill.as_ref().and_then(|color| parseColor(color));
+            let stroke = style.stroke.as_ref().and_then(|color| parseColor(color));
+            let half = style.stroke_width.max(min_width) / 2.0;
+            let mut segments: Vec<(Point, Point)> = vec![];
+            match shape
+            {
+                Shape::Rect { x, y, width, height } =>
+                {
+                    if let Some(color) = fill
+                    {
+                        let (x, y, w, h) = (*x, *y, *width, *height);
+                        paint((x, y), (x + w, y + h), color,
+                              &|p| x <= p.0 && p.0 < x + w && y <= p.1 && p.1 < y + h);
+                    }
+                    let corners = [(*x, *y), (x + width, *y), (x + width, y + height), (*x, y + height)];
+                    segments.extend((0..4).map(|i| (corners[i], corners[(i + 1) % 4])));
+                },
+                Shape::Circle { center, r } =>
+                {
+                    if let Some(color) = fill
+                    {
+                        let (c, r) = (*center, *r);
+                        paint((c.0 - r, c.1 - r), (c.0 + r, c.1 + r), color,
+                              &|p| (p.0 - c.0).powi(2) + (p.1 - c.1).powi(2) <= r * r);
+                    }
+                    if let Some(color) = stroke
+                    {
+                        let (c, r) = (*center, *r);
+                        paint((c.0 - r - half, c.1 - r - half), (c.0 + r + half, c.1 + r + half), color,
+                              &|p| ((p.0 - c.0).hypot(p.1 - c.1) - r).abs() <= half);
+                    }
+                },
+                Shape::Line { from, to } => { segments.push((*from, *to)); },
+                Shape::Polyline { points } =>
+                {
+                    segments.extend(points.windows(2).map(|pair| (pair[0], pair[1])));
+                },
+                Shape::Text { .. } => {},
+            }
+
+            if let (Some(color), false) = (stroke, matches!(shape, Shape::Circle { .. }))
+            {
+                for (a, b) in segments
+                {
+                    paint((a.0.min(b.0) - half, a.1.min(b.1) - half),
+                          (a.0.max(b.0) + half, a.1.max(b.1) + half), color,
+                          &|p| distanceToSegment(p, a, b) <= half);
+                }
+            }
+        }
+        raster
+    }
+}
+
+fn distanceToSegment(p: Point, a: Point, b: Point) -> f64
+{
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 { 0.0 }
+            else { (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0) };
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+impl fmt::Display for Canvas
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let (x, y, width, height) = self.viewBox();
+        write!(f, r#"<svg viewBox="{} {} {} {}""#, x, y, width, height)?;
+        if let Some(scale) = self.scale
+        {
+            write!(f, r#" width="{}" height="{}""#, width * scale, height * scale)?;
+        }
+        writeln!(f, r#" xmlns="http://www.w3.org/2000/svg">"#)?;
+        write!(f, "{}", self.body())?;
+        writeln!(f, "</svg>")
+    }
+}
+
+fn escape(text: &str) -> String
+{
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testStyle()
+{
+    assert_eq!(Style::fill("red").to_string(), r#"fill="red" stroke="none""#);
+    assert_eq!(Style::stroke("blue", 2.0).to_string(), r#"fill="none" stroke="blue" stroke-width="2""#);
+    assert_eq!(Style::fill("red").withStroke("blue", 0.5).to_string(),
+               r#"fill="red" stroke="blue" stroke-width="0.5""#);
+}
+
+#[test]
+fn testViewBox()
+{
+    let mut canvas = Canvas::new();
+    assert_eq!(canvas.viewBox(), (0.0, 0.0, 0.0, 0.0));
+    canvas.cell(-1, 2, 4.0, &Style::fill(palette::RED));
+    canvas.circle((10.0, 0.0), 2.0, &Style::fill(palette::BLUE));
+    assert_eq!(canvas.viewBox(), (-4.0, -2.0, 16.0, 14.0));
+    canvas.setPadding(1.0);
+    assert_eq!(canvas.viewBox(), (-5.0, -3.0, 18.0, 16.0));
+    canvas.setViewBox(0.0, 0.0, 8.0, 8.0);
+    assert_eq!(canvas.viewBox(), (0.0, 0.0, 8.0, 8.0));
+}
+
+#[test]
+fn testRender()
+{
+    let mut canvas = Canvas::new();
+    canvas.setScale(2.0);
+    canvas.layer("top");
+    canvas.text((1.0, 1.0), "<1>", 3.0, &Style::fill("white"));
+    canvas.layer("");
+    canvas.polyline(&[(0.0, 0.0), (2.0, 3.0)], &Style::stroke("red", 1.0));
+    canvas.layer("empty");
+    assert_eq!(canvas.to_string(), r#"<svg viewBox="0 0 2 3" width="4" height="6" xmlns="http://www.w3.org/2000/svg">
+<polyline points="0,0 2,3" fill="none" stroke="red" stroke-width="1" />
+<g id="top">
+<text x="1" y="1" text-anchor="middle" dominant-baseline="middle" font-family="IBM Plex Mono" font-weight="bold" font-size="3" fill="white" stroke="none">&lt;1&gt;</text>
+</g>
+</svg>
+"#);
+}
+
+#[test]
+fn testRasterize()
+{
+    assert_eq!(parseColor("#ff4757"), Some((255, 71, 87)));
+    assert_eq!(parseColor("none"), None);
+
+    let red = (255, 0, 0);
+    let white = (255, 255, 255);
+    let mut canvas = Canvas::new();
+    canvas.setViewBox(0.0, 0.0, 4.0, 3.0);
+    canvas.cell(1, 1, 1.0, &Style::fill("red"));
+    canvas.line((0.0, 0.5), (4.0, 0.5), &Style::stroke("black", 1.0));
+    canvas.text((2.0, 2.0), "ignored", 1.0, &Style::fill("black"));
+    let raster = canvas.rasterize(1.0, white);
+    assert_eq!((raster.width, raster.height), (4, 3));
+    assert_eq!(raster.pixels, vec![(0, 0, 0), (0, 0, 0), (0, 0, 0), (0, 0, 0),
+                                   white, red, white, white,
+                                   white, white, white, white]);
+
+    let raster = canvas.rasterize(2.0, white);
+    assert_eq!((raster.width, raster.height), (8, 6));
+    assert_eq!(raster.pixels[3 * 8 + 2], red);
+    assert_eq!(raster.pixels[3 * 8 + 4], white);
+
+    let mut canvas = Canvas::new();
+    canvas.circle((2.0, 2.0), 2.0, &Style::fill("red"));
+    let raster = canvas.rasterize(1.0, white);
+    assert_eq!(raster.pixels.iter().filter(|p| **p == red).count(), 12);
+}
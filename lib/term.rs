@@ -0,0 +1,273 @@
+#![allow(dead_code)]
+
+// Live pictures in the terminal. Like `vis', it is off by default and
+// the runner turns it on, here with a frame rate. A solution shows a
+// `Screen' of colored cells for every step it wants to be seen, and
+// each one is drawn over the one before, with ANSI escapes. Frames go
+// to stderr, so that the answers on stdout stay clean.
+
+use std::io::{self, Write};
+use std::sync::RwLock;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use super::grid::{Bounds, Coord, Grid};
+use super::svg::{parseColor, Color};
+
+// Time between frames, or None when off.
+static INTERVAL: RwLock<Option<Duration>> = RwLock::new(None);
+
+fn interval(fps: f64) -> Result<Duration, String>
+{
+    if !(fps > 0.0 && fps.is_finite())
+    {
+        return Err(format!("Invalid frame rate: {}", fps));
+    }
+    Ok(Duration::from_secs_f64(1.0 / fps))
+}
+
+// Show frames at `fps' frames per second from now on.
+pub fn enable(fps: f64) -> Result<(), String>
+{
+    *INTERVAL.write().unwrap() = Some(interval(fps)?);
+    Ok(())
+}
+
+pub fn disable()
+{
+    *INTERVAL.write().unwrap() = None;
+}
+
+pub fn enabled() -> bool
+{
+    INTERVAL.read().unwrap().is_some()
+}
+
+// A cell is two columns wide, so that it comes out about square.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cell
+{
+    pub ch: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Cell
+{
+    pub const BLANK: Cell = Cell { ch: ' ', fg: None, bg: None };
+
+    // A cell filled with `color', which is a color of the palette in
+    // `svg'. Unknown colors are left blank.
+    pub fn block(color: &str) -> Self
+    {
+        Cell { ch: ' ', fg: None, bg: parseColor(color) }
+    }
+
+    pub fn glyph(ch: char, color: &str) -> Self
+    {
+        Cell { ch, fg: parseColor(color), bg: None }
+    }
+
+    pub fn on(mut self, color: &str) -> Self
+    {
+        self.bg = parseColor(color);
+        self
+    }
+}
+
+// A grid of cells, with a line of text under it.
+pub struct Screen
+{
+    grid: Grid<Cell>,
+    status: String,
+}
+
+impl Screen
+{
+    pub fn new(bounds: Bounds) -> Self
+    {
+        Screen { grid: Grid::new(bounds, Cell::BLANK), status: String::new() }
+    }
+
+    // Set the cell at `coord', if it is on the screen.
+    pub fn set(&mut self, coord: &Coord, cell: Cell)
+    {
+        self.grid.set(coord, cell);
+    }
+
+    pub fn setStatus(&mut self, status: &str)
+    {
+        self.status = String::from(status);
+    }
+
+    // The screen as text with ANSI colors, one line per row. Every
+    // line clears the rest of itself, so it can be drawn over a
+    // longer one.
+    pub fn render(&self) -> String
+    {
+        fn color(code: u8, color: Option<Color>) -> String
+        {
+            match color
+            {
+                Some((r, g, b)) => format!("\x1b[{};2;{};{};{}m", code, r, g, b),
+                None => String::new(),
+            }
+        }
+
+        let width = self.grid.bounds.width();
+        let cells: Vec<&Cell> = self.grid.iter().map(|(_, cell)| cell).collect();
+        let mut result = String::new();
+        for row in cells.chunks(width.max(1))
+        {
+            let mut style: Option<(Option<Color>, Option<Color>)> = None;
+            for cell in row
+            {
+                if style != Some((cell.fg, cell.bg))
+                {
+                    result += "\x1b[0m";
+                    result += &color(38, cell.fg);
+                    result += &color(48, cell.bg);
+                    style = Some((cell.fg, cell.bg));
+                }
+                result.push(cell.ch);
+                result.push(' ');
+            }
+            result += "\x1b[0m\x1b[K\n";
+        }
+        result += &self.status;
+        result += "\x1b[K\n";
+        result
+    }
+}
+
+// A sequence of screens drawn in place, at most at the frame rate set
+// by the runner. The cursor is hidden while it runs, and comes back
+// when it is finished or dropped.
+pub struct Live
+{
+    interval: Option<Duration>,
+    out: Box<dyn Write>,
+    last: Option<Instant>,
+}
+
+impl Live
+{
+    pub fn new() -> Self
+    {
+        Self::to(*INTERVAL.read().unwrap(), Box::new(io::stderr()))
+    }
+
+    // Show frames at `fps' frames per second, even when the runner did
+    // not turn the terminal on.
+    pub fn withFps(fps: f64) -> Result<Self, String>
+    {
+        Ok(Self::to(Some(interval(fps)?), Box::new(io::stderr())))
+    }
+
+    fn to(interval: Option<Duration>, out: Box<dyn Write>) -> Self
+    {
+        Live { interval, out, last: None }
+    }
+
+    // Draw what `draw' makes as the next frame, after waiting for the
+    // time of the one before to pass. When the terminal is off, `draw'
+    // is not called.
+    pub fn frame<F>(&mut self, draw: F)
+    where F: FnOnce() -> Screen
+    {
+        let interval = match self.interval
+        {
+            Some(interval) => interval,
+            None => return,
+        };
+        let mut text = draw().render();
+        match self.last
+        {
+            // Hide the cursor and start on a clear terminal.
+            None => { text.insert_str(0, "\x1b[?25l\x1b[2J\x1b[H"); },
+            Some(last) =>
+            {
+                if let Some(wait) = interval.checked_sub(last.elapsed())
+                {
+                    thread::sleep(wait);
+                }
+                text.insert_str(0, "\x1b[H");
+            },
+        }
+        text += "\x1b[J";
+        // Like with pictures, a broken terminal does not stop the
+        // solution.
+        if self.out.write_all(text.as_bytes()).and_then(|_| self.out.flush()).is_err()
+        {
+            self.interval = None;
+        }
+        self.last = Some(Instant::now());
+    }
+
+    pub fn finish(mut self)
+    {
+        self.close();
+    }
+
+    fn close(&mut self)
+    {
+        if self.last.take().is_some()
+        {
+            let _ = self.out.write_all(b"\x1b[?25h").and_then(|_| self.out.flush());
+        }
+    }
+}
+
+impl Default for Live
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Drop for Live
+{
+    fn drop(&mut self)
+    {
+        self.close();
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testScreen()
+{
+    let mut screen = Screen::new(Bounds::new((0, 3), (0, 2)));
+    screen.set(&(0, 0), Cell::block("#ff0000"));
+    screen.set(&(1, 0), Cell::block("#ff0000"));
+    screen.set(&(2, 1), Cell::glyph('o', "white").on("black"));
+    screen.set(&(5, 5), Cell::block("black"));
+    screen.setStatus("Score: 1");
+    assert_eq!(screen.render(),
+               "\x1b[0m\x1b[48;2;255;0;0m    \x1b[0m  \x1b[0m\x1b[K\n\
+                \x1b[0m    \x1b[0m\x1b[38;2;255;255;255m\x1b[48;2;0;0;0mo \x1b[0m\x1b[K\n\
+                Score: 1\x1b[K\n");
+}
+
+#[test]
+fn testLive()
+{
+    let mut off = Live::to(None, Box::new(io::sink()));
+    off.frame(|| panic!("Drawn while off"));
+
+    let mut live = Live::to(Some(Duration::from_millis(20)), Box::new(io::sink()));
+    let start = Instant::now();
+    for _ in 0..3
+    {
+        live.frame(|| Screen::new(Bounds::new((0, 1), (0, 1))));
+    }
+    live.finish();
+    // No wait before the first frame.
+    assert!(start.elapsed() >= Duration::from_millis(40));
+
+    assert!(enable(0.0).is_err());
+    assert!(Live::withFps(f64::NAN).is_err());
+}
@@ -28,9 +28,11 @@ impl<T> Vec3<T> where T: ValueTraits<T>
         }
     }
 
+    // The number of components, so never empty.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {3}
 
-    pub fn iter(&self) -> std::slice::Iter<T>
+    pub fn iter(&self) -> std::slice::Iter<'_, T>
     {
         self.data.iter()
     }
@@ -0,0 +1,519 @@
+#![allow(dead_code)]
+
+// Where solutions put their pictures. It is off by default, and the
+// runner turns it on with an output directory. Solutions hand over
+// each picture with a file name, and do not care where it ends up or
+// whether it is written at all. Sequences of pictures go into an
+// `Animation', which becomes one animated file, or a directory of
+// frames.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use super::gif;
+use super::svg::{Canvas, Point};
+
+static OUTPUT: RwLock<Option<PathBuf>> = RwLock::new(None);
+static SETTINGS: RwLock<Settings> = RwLock::new(Settings { format: Format::Svg, frame_ms: 50 });
+
+// What an animation becomes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format
+{
+    // A directory with one SVG per frame.
+    Frames,
+    // One SVG, animated with SMIL. It plays once and stays on the last
+    // frame. Only the shapes that change between frames are written,
+    // but every change stays in the file: thousands of frames with
+    // many changes, like a whole day 13 game, still make a file of
+    // megabytes that browsers are slow to play. Use Gif for those.
+    Svg,
+    // One GIF, which loops.
+    Gif,
+}
+
+impl Format
+{
+    pub fn parse(name: &str) -> Result<Self, String>
+    {
+        match name
+        {
+            "frames" => Ok(Format::Frames),
+            "svg" => Ok(Format::Svg),
+            "gif" => Ok(Format::Gif),
+            _ => Err(format!("Unknown animation format: {}. Use frames, svg or gif.", name)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Settings
+{
+    format: Format,
+    frame_ms: u32,
+}
+
+// Write pictures into `dir' from now on, creating it if needed.
+pub fn enable(dir: &Path) -> Result<(), String>
+{
+    fs::create_dir_all(dir).map_err(|_| format!("Failed to create directory {}.", dir.display()))?;
+    *OUTPUT.write().unwrap() = Some(dir.to_path_buf());
+    Ok(())
+}
+
+pub fn disable()
+{
+    *OUTPUT.write().unwrap() = None;
+}
+
+pub fn enabled() -> bool
+{
+    OUTPUT.read().unwrap().is_some()
+}
+
+pub fn setFormat(format: Format)
+{
+    SETTINGS.write().unwrap().format = format;
+}
+
+// How long each frame of an animation shows.
+pub fn setFrameTime(ms: u32)
+{
+    SETTINGS.write().unwrap().frame_ms = ms;
+}
+
+// Save what `render' draws as `name' in the output directory. `name'
+// can have directories in it, like “13/00001.svg”. When visualization
+// is off, `render' is not even called. Failing to write is reported
+// but does not stop the solution.
+pub fn save<F>(name: &str, render: F)
+where F: FnOnce() -> String
+{
+    let dir = match OUTPUT.read().unwrap().clone()
+    {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    let path = dir.join(name);
+    let result = path.parent().map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, render()));
+    if let Err(e) = result
+    {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+enum Sink
+{
+    Frames(PathBuf),
+    Svg(SvgFrames),
+    Gif(gif::Encoder<BufWriter<File>>),
+}
+
+// A shape of an animated SVG that is showing, as SVG, with the box it
+// covers.
+struct Shown
+{
+    id: usize,
+    svg: String,
+    bounds: (Point, Point),
+}
+
+// The frames of an animated SVG. Each shape is written once, when it
+// shows up, and hidden again by its id when it goes away.
+struct SvgFrames
+{
+    out: BufWriter<File>,
+    // In the order they are in the file, which is the order they are
+    // drawn in.
+    shown: Vec<Shown>,
+    next_id: usize,
+}
+
+fn overlap(a: (Point, Point), b: (Point, Point)) -> bool
+{
+    let ((a_min, a_max), (b_min, b_max)) = (a, b);
+    a_min.0 < b_max.0 && b_min.0 < a_max.0 && a_min.1 < b_max.1 && b_min.1 < a_max.1
+}
+
+// Shapes by the squares of a grid that their boxes touch, to find the
+// ones that may overlap a box quickly. The squares are as big as the
+// smallest shape, which is usually a cell, and the grid covers the
+// shapes of one frame. Shapes that touch a lot of squares are near
+// everything.
+struct Squares
+{
+    size: f64,
+    // The first square, and the number of them across and down.
+    origin: (i64, i64),
+    columns: i64,
+    rows: i64,
+    shapes: Vec<Vec<usize>>,
+    large: Vec<usize>,
+}
+
+// Most squares a shape goes into.
+const MAX_SQUARES: i64 = 64;
+
+impl Squares
+{
+    fn new(elements: &[(String, (Point, Point))]) -> Self
+    {
+        let size = elements.iter().map(|(_, (min, max))| (max.0 - min.0).max(max.1 - min.1))
+            .filter(|size| size.is_finite() && *size > 0.0).fold(f64::INFINITY, f64::min);
+        let mut squares = Squares { size, origin: (0, 0), columns: 0, rows: 0, shapes: vec![], large: vec![] };
+        let ranges: Vec<(i64, i64, i64, i64)> = elements.iter()
+            .filter_map(|(_, bounds)| squares.range(*bounds)).collect();
+        if let (Some(x0), Some(y0), Some(x1), Some(y1)) =
+            (ranges.iter().map(|r| r.0).min(), ranges.iter().map(|r| r.1).min(),
+             ranges.iter().map(|r| r.2).max(), ranges.iter().map(|r| r.3).max())
+        {
+            squares.origin = (x0, y0);
+            squares.columns = x1 - x0 + 1;
+            squares.rows = y1 - y0 + 1;
+            squares.shapes = vec![vec![]; (squares.columns * squares.rows) as usize];
+        }
+        squares
+    }
+
+    // The first and last column and row of the squares that `bounds'
+    // touches, or None if there are too many.
+    fn range(&self, (min, max): (Point, Point)) -> Option<(i64, i64, i64, i64)>
+    {
+        let corners = [min.0 / self.size, min.1 / self.size, max.0 / self.size, max.1 / self.size];
+        if !corners.iter().all(|x| x.is_finite())
+        {
+            return None;
+        }
+        let [x0, y0, x1, y1] = corners.map(|x| x.floor() as i64);
+        if (x1 - x0 + 1) * (y1 - y0 + 1) > MAX_SQUARES
+        {
+            return None;
+        }
+        Some((x0, y0, x1, y1))
+    }
+
+    // Indices into `shapes' of the squares that `bounds' touches.
+    fn squares(&self, bounds: (Point, Point)) -> Option<impl Iterator<Item = usize>>
+    {
+        let (x0, y0, x1, y1) = self.range(bounds)?;
+        let (origin, columns) = (self.origin, self.columns);
+        Some((y0..=y1).flat_map(move |y| (x0..=x1).map(move |x| ((y - origin.1) * columns + x - origin.0) as usize)))
+    }
+
+    fn insert(&mut self, index: usize, bounds: (Point, Point))
+    {
+        match self.squares(bounds)
+        {
+            Some(squares) => for square in squares
+            {
+                self.shapes[square].push(index);
+            },
+            None => { self.large.push(index); },
+        }
+    }
+
+    // Shapes that may overlap `bounds', maybe more than once.
+    fn near(&self, bounds: (Point, Point)) -> Box<dyn Iterator<Item = usize> + '_>
+    {
+        match self.squares(bounds)
+        {
+            Some(squares) => Box::new(squares.flat_map(move |square| &self.shapes[square])
+                                      .chain(&self.large).cloned()),
+            None => Box::new(self.shapes.iter().flatten().chain(&self.large).cloned()),
+        }
+    }
+}
+
+impl SvgFrames
+{
+    // Show `elements' from `begin_ms' on, instead of the frame before.
+    fn frame(&mut self, elements: Vec<(String, (Point, Point))>, begin_ms: usize) -> io::Result<()>
+    {
+        // Keep the shapes that look the same as a showing one.
+        let mut showing: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, shown) in self.shown.iter().enumerate().rev()
+        {
+            showing.entry(&shown.svg).or_default().push(i);
+        }
+        let mut kept: Vec<Option<usize>> = elements.iter()
+            .map(|(svg, _)| showing.get_mut(&svg[..]).and_then(|found| found.pop())).collect();
+
+        // The new ones go after everything that is kept, so a kept
+        // shape has to be written again when it should be drawn over
+        // a new one, or over a kept one that comes after it in the
+        // file. Shapes that do not overlap can be in any order.
+        let mut earlier = Squares::new(&elements);
+        for j in 0..elements.len()
+        {
+            if let Some(i) = kept[j]
+            {
+                let under = earlier.near(elements[j].1).any(
+                    |p| kept[p].is_none_or(|q| q > i) && overlap(elements[p].1, elements[j].1));
+                if under
+                {
+                    kept[j] = None;
+                }
+            }
+            earlier.insert(j, elements[j].1);
+        }
+
+        let keep: HashSet<usize> = kept.iter().flatten().cloned().collect();
+        let mut shown = vec![];
+        for (i, old) in self.shown.drain(..).enumerate()
+        {
+            if keep.contains(&i)
+            {
+                shown.push(old);
+            }
+            else
+            {
+                writeln!(self.out, r##"<set xlink:href="#e{}" attributeName="visibility" to="hidden" begin="{}ms" />"##,
+                         old.id, begin_ms)?;
+            }
+        }
+        for ((svg, bounds), _) in elements.into_iter().zip(kept).filter(|(_, keep)| keep.is_none())
+        {
+            writeln!(self.out, r#"<g id="e{}" visibility="hidden"><set attributeName="visibility" to="visible" begin="{}ms" />{}</g>"#,
+                     self.next_id, begin_ms, svg)?;
+            shown.push(Shown { id: self.next_id, svg, bounds });
+            self.next_id += 1;
+        }
+        self.shown = shown;
+        Ok(())
+    }
+}
+
+// A sequence of pictures, like the frames of a game. The frames are
+// written as they come, so that a long animation does not fill up the
+// memory. The animation is complete when it is finished or dropped.
+pub struct Animation
+{
+    // Where the animation goes without an extension, or None when it
+    // is not drawn.
+    path: Option<PathBuf>,
+    settings: Settings,
+    sink: Option<Sink>,
+    count: usize,
+}
+
+impl Animation
+{
+    // An animation called `name' in the output directory, in the
+    // format and with the frame time set by the runner.
+    pub fn new(name: &str) -> Self
+    {
+        let path = OUTPUT.read().unwrap().as_ref().map(|dir| dir.join(name));
+        Self::at(path, *SETTINGS.read().unwrap())
+    }
+
+    fn at(path: Option<PathBuf>, settings: Settings) -> Self
+    {
+        Animation { path, settings, sink: None, count: 0 }
+    }
+
+    // Add what `draw' draws as the next frame. When visualization is
+    // off, `draw' is not called. Like with `save', failing to write is
+    // reported, and the rest of the animation is left out.
+    pub fn frame<F>(&mut self, draw: F)
+    where F: FnOnce() -> Canvas
+    {
+        let path = match &self.path
+        {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        if let Err(e) = self.write(&path, draw())
+        {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+            self.path = None;
+            self.sink = None;
+        }
+        self.count += 1;
+    }
+
+    fn write(&mut self, path: &Path, canvas: Canvas) -> io::Result<()>
+    {
+        let frame_ms = self.settings.frame_ms;
+        if self.sink.is_none()
+        {
+            self.sink = Some(self.open(path, &canvas)?);
+        }
+        match self.sink.as_mut().unwrap()
+        {
+            Sink::Frames(dir) => fs::write(dir.join(format!("{:05}.svg", self.count)), canvas.to_string()),
+            Sink::Svg(frames) => frames.frame(canvas.elements(), frame_ms as usize * self.count),
+            Sink::Gif(encoder) =>
+            {
+                let raster = canvas.rasterize(canvas.scale().unwrap_or(1.0), (255, 255, 255));
+                encoder.frame(&raster, frame_ms)
+            },
+        }
+    }
+
+    // Start the output, with the size of the first frame.
+    fn open(&self, path: &Path, first: &Canvas) -> io::Result<Sink>
+    {
+        if let Some(dir) = path.parent()
+        {
+            fs::create_dir_all(dir)?;
+        }
+        let create = |extension| File::create(path.with_extension(extension)).map(BufWriter::new);
+        match self.settings.format
+        {
+            Format::Frames =>
+            {
+                fs::create_dir_all(path)?;
+                Ok(Sink::Frames(path.to_path_buf()))
+            },
+            Format::Svg =>
+            {
+                let mut out = create("svg")?;
+                let (x, y, width, height) = first.viewBox();
+                write!(out, r#"<svg viewBox="{} {} {} {}""#, x, y, width, height)?;
+                if let Some(scale) = first.scale()
+                {
+                    write!(out, r#" width="{}" height="{}""#, width * scale, height * scale)?;
+                }
+                writeln!(out, r#" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#)?;
+                Ok(Sink::Svg(SvgFrames { out, shown: vec![], next_id: 0 }))
+            },
+            Format::Gif =>
+            {
+                let raster = first.rasterize(first.scale().unwrap_or(1.0), (255, 255, 255));
+                let size = |n: usize| n.min(u16::MAX as usize) as u16;
+                Ok(Sink::Gif(gif::Encoder::new(create("gif")?, size(raster.width),
+                                               size(raster.height))?))
+            },
+        }
+    }
+
+    // Complete the file. Failing to is reported like in `frame'.
+    pub fn finish(mut self)
+    {
+        self.close();
+    }
+
+    fn close(&mut self)
+    {
+        let result = match self.sink.take()
+        {
+            None | Some(Sink::Frames(_)) => Ok(()),
+            Some(Sink::Svg(mut frames)) =>
+                writeln!(frames.out, "</svg>").and_then(|_| frames.out.flush()),
+            Some(Sink::Gif(encoder)) => encoder.finish().map(|_| ()),
+        };
+        if let (Err(e), Some(path)) = (result, &self.path)
+        {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl Drop for Animation
+{
+    fn drop(&mut self)
+    {
+        self.close();
+    }
+}
+
+// ========== Tests =================================================>
+
+#[test]
+fn testSave()
+{
+    let dir = std::env::temp_dir().join(format!("advent-vis-{}", std::process::id()));
+    save("off.svg", || panic!("Rendered while off"));
+    assert!(!enabled());
+
+    enable(&dir).unwrap();
+    assert!(enabled());
+    save("13/00001.svg", || String::from("<svg/>"));
+    assert_eq!(fs::read_to_string(dir.join("13").join("00001.svg")).unwrap(), "<svg/>");
+
+    disable();
+    save("after.svg", || String::from("<svg/>"));
+    assert!(!dir.join("after.svg").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn testAnimation()
+{
+    let dir = std::env::temp_dir().join(format!("advent-animation-{}", std::process::id()));
+    let draw = |i: i64|
+    {
+        let mut canvas = Canvas::new();
+        canvas.setViewBox(0.0, 0.0, 4.0, 2.0);
+        canvas.cell(i, 0, 1.0, &super::svg::Style::fill("black"));
+        canvas
+    };
+
+    let mut off = Animation::at(None, Settings { format: Format::Svg, frame_ms: 50 });
+    off.frame(|| panic!("Drawn while off"));
+    off.finish();
+
+    let mut animation = Animation::at(Some(dir.join("svg")), Settings { format: Format::Svg, frame_ms: 40 });
+    for i in 0..3
+    {
+        animation.frame(|| draw(i));
+    }
+    animation.finish();
+    let text = fs::read_to_string(dir.join("svg.svg")).unwrap();
+    assert!(text.starts_with(r#"<svg viewBox="0 0 4 2""#));
+    assert_eq!(text.matches("<g ").count(), 3);
+    assert!(text.contains(r##"<set xlink:href="#e0" attributeName="visibility" to="hidden" begin="40ms" />"##));
+    assert!(text.contains(r#"<g id="e2" visibility="hidden"><set attributeName="visibility" to="visible" begin="80ms" />"#));
+    // The last frame stays.
+    assert!(!text.contains(r##"xlink:href="#e2""##));
+    assert!(text.ends_with("</svg>\n"));
+
+    // What does not change is written once, and a shape that a new one
+    // would cover is written again on top.
+    let cells = |cells: &[(i64, &str)]|
+    {
+        let mut canvas = Canvas::new();
+        canvas.setViewBox(0.0, 0.0, 4.0, 2.0);
+        canvas.rect(0.0, 0.0, 4.0, 2.0, &super::svg::Style::fill("white"));
+        for (x, color) in cells
+        {
+            canvas.cell(*x, 0, 1.0, &super::svg::Style::fill(color));
+        }
+        canvas
+    };
+    let mut animation = Animation::at(Some(dir.join("diff")), Settings { format: Format::Svg, frame_ms: 40 });
+    animation.frame(|| cells(&[(0, "red"), (1, "blue")]));
+    animation.frame(|| cells(&[(0, "red"), (2, "black"), (1, "blue")]));
+    animation.frame(|| cells(&[(1, "black"), (0, "red"), (1, "blue")]));
+    animation.finish();
+    let text = fs::read_to_string(dir.join("diff.svg")).unwrap();
+    assert_eq!(text.matches("<g ").count(), 6);
+    assert_eq!(text.matches(r#"fill="white""#).count(), 1);
+    assert_eq!(text.matches(r#"fill="red""#).count(), 1);
+    assert_eq!(text.matches(r#"fill="blue""#).count(), 2);
+    assert!(text.contains(r##"<set xlink:href="#e2" attributeName="visibility" to="hidden" begin="80ms" />"##));
+    assert!(text.contains(r##"<set xlink:href="#e3" attributeName="visibility" to="hidden" begin="80ms" />"##));
+
+    let mut animation = Animation::at(Some(dir.join("gif")), Settings { format: Format::Gif, frame_ms: 40 });
+    animation.frame(|| draw(0));
+    animation.frame(|| draw(1));
+    drop(animation);
+    let bytes = fs::read(dir.join("gif.gif")).unwrap();
+    assert_eq!(&bytes[..10], b"GIF89a\x04\x00\x02\x00");
+    assert_eq!(*bytes.last().unwrap(), 0x3b);
+
+    let mut animation = Animation::at(Some(dir.join("frames")), Settings { format: Format::Frames, frame_ms: 40 });
+    animation.frame(|| draw(0));
+    animation.frame(|| draw(1));
+    animation.finish();
+    assert!(dir.join("frames").join("00001.svg").exists());
+
+    assert_eq!(Format::parse("gif"), Ok(Format::Gif));
+    assert!(Format::parse("png").is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
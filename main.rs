@@ -1,37 +1,704 @@
 #![allow(non_snake_case)]
 
-use std::io::{self, prelude::*};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use std::fs;
+use std::panic;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use std::env;
 use std::process::exit;
 
-mod lib
+use advent_2019::{lib, intcode, makeIntEnum};
+use lib::{stats, term, vis};
+use advent::{registry, inputs};
+
+mod advent
 {
-    pub mod vector;
-    pub mod ratio;
-    pub mod meta;
+    pub mod registry;
+    pub mod inputs;
+    pub mod day01;
+    pub mod day02;
+    pub mod day03;
+    pub mod day04;
+    pub mod day05;
+    pub mod day06;
+    pub mod day07;
+    pub mod day09;
+    pub mod day10;
+    pub mod day11;
+    pub mod day12;
+    pub mod day13;
+    pub mod day15;
 }
 
-mod intcode
+fn usage(name: &String)
 {
-    pub mod intcode;
+    println!("Usage: {name} [--json] [VIS OPTIONS] [--term [--fps N]] DAY PART [--input FILE]...
+       {name} list
+       {name} [--json] [VIS OPTIONS] [-j N] all
+       {name} verify [-j N] [ANSWERS]
+       {name} bench DAY PART [OPTIONS]
+       {name} play 13 [--fps N] [--input FILE]
+
+With DAY and PART, run that part of that day on its input in inputs/,
+found by the day number, like inputs/05.txt. With --input, run on FILE
+instead, or on each FILE if there are several; - is stdin.
+
+With list, print the days that have solutions. With all, run every
+part of every day on its input in inputs/, and print a table of the
+answers. With verify, also compare the answers with the known ones in
+ANSWERS (answers.txt by default), and fail if any is different. All
+and verify run N parts at the same time with -j N, one per CPU by
+default; use -j 1 for run times without the others in the way.
+
+With --json, print the answers of DAY PART or all as JSON instead,
+with the day, part, answer, duration in ms and error of each part.
+With --vis, the days that draw pictures of what they do write them
+into DIR; by default nothing is drawn. A sequence of pictures, like
+the frames of a game, becomes one animation. With --term, the days
+that can show what they do in the terminal draw it on stderr as they
+go, at N frames per second with --fps N, 30 by default.
+
+With play, play the game of day 13 on the keyboard in the terminal,
+at N ticks per second with --fps N, 10 by default. Move the paddle
+with the arrow keys, or a and d. Press s to save the game, r to go
+back to where it was saved, and q or Ctrl-C to quit.
+
+With bench, run that part of that day on its input in inputs/ many
+times, and print statistics of the run time.
+
+Bench options:
+  --runs N              Run N times. Default is 10, unless --time is
+                        given.
+  --time SECONDS        Run until SECONDS have passed.
+  --baseline FILE       Compare the median with the one in FILE, and
+                        fail if it got slower.
+  --tolerance PERCENT   How much slower than the baseline is fine.
+                        Default is 10.
+  --save FILE           Save the median to FILE as a baseline.
+
+Vis options:
+  --vis DIR             Write pictures into DIR.
+  --vis-format FORMAT   How sequences of pictures are written: svg for
+                        an animated SVG, gif for a GIF, or frames for a
+                        directory of SVGs. Default is svg.
+  --frame-ms MS         Show each frame of an animation for MS
+                        milliseconds. Default is 50.
+
+Example: {name} 4 1  # Run day 4 part 1.", name=name)
 }
 
-extern crate meta;
+// Milliseconds in `duration'.
+fn millis(duration: Duration) -> f64
+{
+    (duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9) * 1000.0
+}
 
-meta::importAdvent!();
-meta::defineRun!();
+// Run a part of a day, turning a panic into an error.
+fn runCatching(day: u8, part: u8, input: &str) -> Result<String, String>
+{
+    panic::catch_unwind(|| registry::run(day, part, input)).unwrap_or_else(
+        |payload|
+        {
+            if let Some(msg) = payload.downcast_ref::<&str>()
+            {
+                Err(String::from(*msg))
+            }
+            else if let Some(msg) = payload.downcast_ref::<String>()
+            {
+                Err(msg.clone())
+            }
+            else
+            {
+                Err(String::from("Panicked"))
+            }
+        })
+}
 
-fn usage(name: &String)
+// The result of running one part of one day.
+struct Outcome
 {
-    println!("Usage: {name} DAY PART
+    day: u8,
+    part: u8,
+    // Where the input came from, if it was found.
+    input: Option<PathBuf>,
+    // The answer on one line, or what went wrong.
+    result: Result<String, String>,
+    // None if it did not run at all.
+    duration: Option<Duration>,
+}
 
-Example: {name} 4 1  # Run day 4 part 1.", name=name)
+// Quote `s' as a JSON string.
+fn jsonString(s: &str) -> String
+{
+    let mut result = String::from("\"");
+    for c in s.chars()
+    {
+        match c
+        {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            '\r' => result += "\\r",
+            '\t' => result += "\\t",
+            c if (c as u32) < 0x20 => result += &format!("\\u{:04x}", c as u32),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+impl Outcome
+{
+    // The outcome as a JSON object. Fields that do not apply are null.
+    fn toJson(&self) -> String
+    {
+        let answer = self.result.as_ref().map(|answer| jsonString(answer));
+        let error = self.result.as_ref().err().map(|msg| jsonString(msg));
+        let input = self.input.as_ref().map(|path| jsonString(&path.display().to_string()));
+        format!(r#"{{"day": {}, "part": {}, "input": {}, "answer": {}, "duration_ms": {}, "error": {}}}"#,
+                self.day, self.part, input.unwrap_or(String::from("null")),
+                answer.unwrap_or(String::from("null")),
+                self.duration.map(|d| millis(d).to_string()).unwrap_or(String::from("null")),
+                error.unwrap_or(String::from("null")))
+    }
+}
+
+// Run one part of one day on the input at `path', or fail to.
+fn runJob(day: u8, part: u8, path: &Result<PathBuf, String>) -> Outcome
+{
+    let input_raw = path.clone().and_then(|path| inputs::read(&path));
+    let (result, duration) = match input_raw
+    {
+        Ok(input_raw) =>
+        {
+            let begin = Instant::now();
+            let result = runCatching(day, part, &input_raw);
+            (result, Some(begin.elapsed()))
+        },
+        Err(msg) => (Err(msg), None),
+    };
+    let result = result.map(|answer| answer.lines().collect::<Vec<&str>>().join(" "));
+    Outcome { day, part, input: path.clone().ok(), result, duration }
+}
+
+// Run every part of every day, `threads' parts at a time. The
+// outcomes are in order of day and part, however the runs finish.
+fn runEverything(threads: usize) -> Vec<Outcome>
+{
+    // Failures are reported with the outcomes.
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut jobs: Vec<(u8, u8, Result<PathBuf, String>)> = vec![];
+    for solved in registry::DAYS
+    {
+        let path = inputs::find(Path::new(inputs::DIR), solved.num);
+        for part in 1..=solved.parts.len() as u8
+        {
+            jobs.push((solved.num, part, path.clone()));
+        }
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut outcomes: Vec<(usize, Outcome)> = thread::scope(
+        |scope|
+        {
+            let workers: Vec<_> = (0..threads.max(1)).map(
+                |_| scope.spawn(
+                    ||
+                    {
+                        let mut done = vec![];
+                        loop
+                        {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            if index >= jobs.len()
+                            {
+                                return done;
+                            }
+                            let (day, part, path) = &jobs[index];
+                            done.push((index, runJob(*day, *part, path)));
+                        }
+                    })).collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+    outcomes.sort_by_key(|(index, _)| *index);
+
+    let _ = panic::take_hook();
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+// Take the first option called one of `names' out of `args', and
+// return its value.
+fn takeOption(args: &mut Vec<String>, names: &[&str]) -> Result<Option<String>, String>
+{
+    match args.iter().position(|arg| names.contains(&&arg[..]))
+    {
+        Some(i) =>
+        {
+            let value = args.get(i + 1).ok_or(format!("Option {} needs a value", args[i]))?.clone();
+            args.drain(i..i + 2);
+            Ok(Some(value))
+        },
+        None => Ok(None),
+    }
+}
+
+// Take `-j N' out of `args', the number of parts to run at the same
+// time. By default, one per CPU.
+fn takeJobs(args: &mut Vec<String>) -> Result<usize, String>
+{
+    match takeOption(args, &["-j", "--jobs"])?
+    {
+        Some(value) => value.parse::<usize>().ok().filter(|n| *n > 0)
+            .ok_or(format!("Invalid number of jobs: {}", value)),
+        None => Ok(thread::available_parallelism().map(|n| n.get()).unwrap_or(1)),
+    }
+}
+
+fn printRow(outcome: &Outcome, answer: &str, status: &str)
+{
+    let time = outcome.duration.map(|d| format!("{:.3}ms", millis(d))).unwrap_or(String::new());
+    println!("{:>3}  {:>4}  {:<20}  {:>12}  {}", outcome.day, outcome.part, answer, time, status);
+}
+
+// Run everything, and return whether all of it worked.
+fn runAll(json: bool, threads: usize) -> bool
+{
+    if json
+    {
+        let outcomes = runEverything(threads);
+        println!("[\n{}\n]", outcomes.iter().map(|outcome| format!("  {}", outcome.toJson()))
+                 .collect::<Vec<String>>().join(",\n"));
+        return outcomes.iter().all(|outcome| outcome.result.is_ok());
+    }
+
+    println!("{:>3}  {:>4}  {:<20}  {:>12}", "Day", "Part", "Answer", "Time");
+    let outcomes = runEverything(threads);
+    let mut failures = 0;
+    for outcome in &outcomes
+    {
+        match &outcome.result
+        {
+            Ok(answer) => printRow(outcome, answer, ""),
+            Err(msg) =>
+            {
+                failures += 1;
+                printRow(outcome, &format!("FAILED: {}", msg), "");
+            },
+        }
+    }
+
+    if failures > 0
+    {
+        println!("{} of {} parts failed.", failures, outcomes.len());
+    }
+    failures == 0
+}
+
+// Read a table of values by day and part from `path'. Each line is
+// “DAY PART VALUE”, and lines starting with # are comments.
+fn readTable(path: &str) -> Result<HashMap<(u8, u8), String>, String>
+{
+    let text = fs::read_to_string(path).map_err(|_| format!("Failed to read file {}.", path))?;
+    let mut table = HashMap::new();
+    for line in text.lines()
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#')
+        {
+            continue;
+        }
+
+        let mut parts = line.splitn(3, char::is_whitespace);
+        let mut number = || -> Result<u8, String>
+        {
+            parts.next().and_then(|x| x.parse().ok())
+                .ok_or(format!("Invalid line in {}: {}", path, line))
+        };
+        let key = (number()?, number()?);
+        let value = parts.next().ok_or(format!("Invalid line in {}: {}", path, line))?;
+        table.insert(key, String::from(value.trim()));
+    }
+    Ok(table)
+}
+
+// Run everything and compare with the answers in `path'. Return
+// whether all known answers came out right.
+fn verify(path: &str, threads: usize) -> Result<bool, String>
+{
+    let answers = readTable(path)?;
+    println!("{:>3}  {:>4}  {:<20}  {:>12}  {}", "Day", "Part", "Answer", "Time", "Status");
+    let outcomes = runEverything(threads);
+    let mut passes = 0;
+    let mut failures = 0;
+    for outcome in &outcomes
+    {
+        let expected = answers.get(&(outcome.day, outcome.part));
+        match (&outcome.result, expected)
+        {
+            (Ok(answer), Some(expected)) if answer == expected =>
+            {
+                passes += 1;
+                printRow(outcome, answer, "PASS");
+            },
+            (Ok(answer), Some(expected)) =>
+            {
+                failures += 1;
+                printRow(outcome, answer, &format!("FAIL, expected {}", expected));
+            },
+            (Ok(answer), None) => printRow(outcome, answer, "no known answer"),
+            (Err(msg), _) =>
+            {
+                failures += 1;
+                printRow(outcome, &format!("FAILED: {}", msg), "FAIL");
+            },
+        }
+    }
+
+    println!("{} passed, {} failed.", passes, failures);
+    Ok(failures == 0)
+}
+
+struct BenchOptions
+{
+    day: u8,
+    part: u8,
+    runs: Option<usize>,
+    budget: Option<Duration>,
+    baseline: Option<String>,
+    save: Option<String>,
+    // How much slower than the baseline is still fine, in percent.
+    tolerance: f64,
+}
+
+fn parseBenchArgs(args: &Vec<String>) -> Result<BenchOptions, String>
+{
+    if args.len() < 4
+    {
+        return Err(String::from("Missing DAY or PART"));
+    }
+    let mut options = BenchOptions
+    {
+        day: args[2].parse().map_err(|_| format!("Invalid day: {}", args[2]))?,
+        part: args[3].parse().map_err(|_| format!("Invalid part: {}", args[3]))?,
+        runs: None,
+        budget: None,
+        baseline: None,
+        save: None,
+        tolerance: 10.0,
+    };
+
+    let mut i = 4;
+    while i < args.len()
+    {
+        let arg = &args[i][..];
+        let value = args.get(i + 1).ok_or(format!("Option {} needs a value", arg))?;
+        let number = || value.parse::<f64>().map_err(|_| format!("Invalid number: {}", value));
+        match arg
+        {
+            "--runs" => { options.runs = Some(number()? as usize); },
+            "--time" =>
+            {
+                let budget = Duration::try_from_secs_f64(number()?).ok().filter(|d| !d.is_zero())
+                    .ok_or(format!("Invalid time: {}", value))?;
+                options.budget = Some(budget);
+            },
+            "--baseline" => { options.baseline = Some(value.clone()); },
+            "--save" => { options.save = Some(value.clone()); },
+            "--tolerance" => { options.tolerance = number()?; },
+            _ => { return Err(format!("Unexpected argument: {}", arg)); },
+        }
+        i += 2;
+    }
+
+    if options.runs.is_none() && options.budget.is_none()
+    {
+        options.runs = Some(10);
+    }
+    Ok(options)
+}
+
+// Write the median of a day and part into the baseline file `path',
+// keeping what is there for others.
+fn saveBaseline(path: &str, day: u8, part: u8, median: f64) -> Result<(), String>
+{
+    let mut table = readTable(path).unwrap_or_default();
+    table.insert((day, part), format!("{:.3}", median));
+    let mut keys: Vec<&(u8, u8)> = table.keys().collect();
+    keys.sort();
+
+    let mut content = String::from("# Median run times in ms, as “DAY PART TIME”.\n");
+    for key in keys
+    {
+        content += &format!("{} {} {}\n", key.0, key.1, table[key]);
+    }
+    fs::write(path, content).map_err(|_| format!("Failed to write file {}.", path))
+}
+
+// Run a day and part many times, and report how long it takes. Return
+// whether it is not slower than the baseline.
+fn bench(options: &BenchOptions) -> Result<bool, String>
+{
+    registry::solver(options.day, options.part)?;
+    let input = inputs::read(&inputs::find(Path::new(inputs::DIR), options.day)?)?;
+
+    // Warm up caches and the allocator, and check that it works at
+    // all.
+    runCatching(options.day, options.part, &input)?;
+
+    let mut samples: Vec<f64> = vec![];
+    let begin = Instant::now();
+    loop
+    {
+        if options.runs.map_or(false, |runs| samples.len() >= runs)
+            || options.budget.map_or(false, |budget| begin.elapsed() >= budget)
+        {
+            break;
+        }
+
+        let run_begin = Instant::now();
+        registry::run(options.day, options.part, &input)?;
+        samples.push(millis(run_begin.elapsed()));
+    }
+
+    let stats = stats::Stats::fromSamples(&samples).ok_or(String::from("No runs finished"))?;
+    println!("Day {} part {}, {} runs after a warm-up:", options.day, options.part, stats.count);
+    println!("    min     {:>12.3}ms", stats.min);
+    println!("    median  {:>12.3}ms", stats.median);
+    println!("    mean    {:>12.3}ms", stats.mean);
+    println!("    stddev  {:>12.3}ms", stats.stddev);
+
+    let mut ok = true;
+    if let Some(path) = &options.baseline
+    {
+        let table = readTable(path)?;
+        match table.get(&(options.day, options.part)).map(|x| x.parse::<f64>())
+        {
+            Some(Ok(baseline)) =>
+            {
+                let change = (stats.median / baseline - 1.0) * 100.0;
+                ok = change <= options.tolerance;
+                println!("Baseline median {:.3}ms, {:+.1}%{}", baseline, change,
+                         if ok { "" } else { ", REGRESSION" });
+            },
+            Some(Err(_)) => { return Err(format!("Invalid baseline in {}", path)); },
+            None => { println!("No baseline for this day and part."); },
+        }
+    }
+
+    if let Some(path) = &options.save
+    {
+        saveBaseline(path, options.day, options.part, stats.median)?;
+    }
+    Ok(ok)
+}
+
+// Print the days with solutions.
+fn listDays()
+{
+    for day in registry::DAYS
+    {
+        println!("{:>3}  {} part{}  {}", day.num, day.parts.len(),
+                 if day.parts.len() == 1 { "" } else { "s" }, day.title);
+    }
+}
+
+// Take every `--input PATH' out of `args'.
+fn takeInputs(args: &mut Vec<String>) -> Result<Vec<PathBuf>, String>
+{
+    let mut paths = vec![];
+    while let Some(path) = takeOption(args, &["--input"])?
+    {
+        paths.push(PathBuf::from(path));
+    }
+    Ok(paths)
+}
+
+// Run a part of a day on each input in `paths', or on the one in
+// inputs/ if there is none. Return whether all runs worked.
+fn runSingle(day: u8, part: u8, mut paths: Vec<PathBuf>, json: bool) -> Result<bool, String>
+{
+    registry::solver(day, part)?;
+    if paths.is_empty()
+    {
+        paths.push(inputs::find(Path::new(inputs::DIR), day)?);
+    }
+
+    if json
+    {
+        panic::set_hook(Box::new(|_| {}));
+        let outcomes: Vec<Outcome> = paths.into_iter().map(|path| runJob(day, part, &Ok(path)))
+            .collect();
+        if outcomes.len() == 1
+        {
+            println!("{}", outcomes[0].toJson());
+        }
+        else
+        {
+            println!("[\n{}\n]", outcomes.iter().map(|outcome| format!("  {}", outcome.toJson()))
+                     .collect::<Vec<String>>().join(",\n"));
+        }
+        return Ok(outcomes.iter().all(|outcome| outcome.result.is_ok()));
+    }
+
+    let several = paths.len() > 1;
+    for path in paths
+    {
+        let input = inputs::read(&path)?;
+        if several
+        {
+            println!("{}:", path.display());
+        }
+
+        let begin = Instant::now();
+        let output = registry::run(day, part, &input)?;
+        let duration = begin.elapsed();
+
+        println!("{}", output);
+        println!("Run time: {}ms", millis(duration));
+    }
+    Ok(true)
+}
+
+// Play the game of a day on the keyboard, and print the score.
+fn play(args: &mut Vec<String>, fps: f64) -> Result<(), String>
+{
+    let mut paths = takeInputs(args)?;
+    if args.len() != 3
+    {
+        usage(&args[0]);
+        return Err(String::from("Which day to play?"));
+    }
+    let day: u8 = args[2].parse().map_err(|_| format!("Invalid day: {}", args[2]))?;
+    if day != 13
+    {
+        return Err(format!("Day {} has no game to play.", day));
+    }
+    if paths.len() > 1
+    {
+        return Err(String::from("Only one game at a time."));
+    }
+    let path = match paths.pop()
+    {
+        Some(path) => path,
+        None => inputs::find(Path::new(inputs::DIR), day)?,
+    };
+    let input = inputs::read(&path)?;
+    let input = registry::find(day).map_or(&input[..], |day| day.prepare(&input));
+    let score = advent::day13::playInteractive(input, fps)?;
+    println!("Score: {}", score);
+    Ok(())
 }
 
 fn main()
 {
     let args: Vec<String> = env::args().collect();
+    let json = args.iter().any(|arg| arg == "--json");
+    let mut args: Vec<String> = args.into_iter().filter(|arg| arg != "--json").collect();
+    let threads = takeJobs(&mut args).unwrap_or_else(
+        |msg|
+        {
+            eprintln!("{}", msg);
+            exit(1);
+        });
+    // Pictures are only drawn when asked for.
+    let vis_dir = takeOption(&mut args, &["--vis"])
+        .and_then(|dir| dir.map_or(Ok(()), |dir| vis::enable(Path::new(&dir))));
+    let vis_format = takeOption(&mut args, &["--vis-format"])
+        .and_then(|name| name.map_or(Ok(()), |name| vis::Format::parse(&name).map(vis::setFormat)));
+    let frame_ms = takeOption(&mut args, &["--frame-ms"]).and_then(
+        |ms| ms.map_or(Ok(()), |ms| ms.parse().map(vis::setFrameTime)
+                       .map_err(|_| format!("Invalid frame time: {}", ms))));
+    if let Err(msg) = vis_dir.and(vis_format).and(frame_ms)
+    {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    // So is the terminal, which only makes sense for one part at a
+    // time.
+    let live = args.iter().any(|arg| arg == "--term");
+    args.retain(|arg| arg != "--term");
+    let fps = takeOption(&mut args, &["--fps"]).and_then(
+        |fps| fps.map_or(Ok(None), |fps| fps.parse::<f64>().map(Some)
+                         .map_err(|_| format!("Invalid frame rate: {}", fps))))
+        .and_then(|fps| match args.get(1).map(|arg| &arg[..])
+                  {
+                      Some("verify") | Some("bench") | Some("list") | Some("all") if live =>
+                          Err(String::from("--term only works with DAY PART.")),
+                      _ => Ok(fps),
+                  });
+    let fps = fps.unwrap_or_else(
+        |msg|
+        {
+            eprintln!("{}", msg);
+            exit(1);
+        });
+    if (args.len() == 2 || args.len() == 3) && args[1] == "verify"
+    {
+        let path = args.get(2).map(|p| &p[..]).unwrap_or("answers.txt");
+        match verify(path, threads)
+        {
+            Ok(true) => { return; },
+            Ok(false) => { exit(1); },
+            Err(msg) =>
+            {
+                eprintln!("{}", msg);
+                exit(1);
+            },
+        }
+    }
+
+    if args.len() > 1 && args[1] == "bench"
+    {
+        let result = parseBenchArgs(&args).map_err(|msg| { usage(&args[0]); msg })
+            .and_then(|options| bench(&options));
+        match result
+        {
+            Ok(true) => { return; },
+            Ok(false) => { exit(1); },
+            Err(msg) =>
+            {
+                eprintln!("{}", msg);
+                exit(1);
+            },
+        }
+    }
+
+    if args.len() > 1 && args[1] == "play"
+    {
+        if let Err(msg) = play(&mut args, fps.unwrap_or(10.0))
+        {
+            eprintln!("{}", msg);
+            exit(1);
+        }
+        return;
+    }
+
+    if args.len() == 2 && args[1] == "list"
+    {
+        listDays();
+        return;
+    }
+
+    if args.len() == 2 && args[1] == "all"
+    {
+        if !runAll(json, threads)
+        {
+            exit(1);
+        }
+        return;
+    }
+
+    let paths = takeInputs(&mut args).unwrap_or_else(
+        |msg|
+        {
+            eprintln!("{}", msg);
+            exit(1);
+        });
     if args.len() != 3
     {
         usage(&args[0]);
@@ -40,17 +707,82 @@ fn main()
 
     let day: u8 = args[1].parse().unwrap_or_else(|_| {usage(&args[0]); 0});
     let part: u8 = args[2].parse().unwrap_or_else(|_| {usage(&args[0]); 0});
+    if live
+    {
+        if let Err(msg) = term::enable(fps.unwrap_or(30.0))
+        {
+            eprintln!("{}", msg);
+            exit(1);
+        }
+    }
+    match runSingle(day, part, paths, json)
+    {
+        Ok(true) => {},
+        Ok(false) => { exit(1); },
+        Err(msg) =>
+        {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    }
+}
 
-    let mut input_raw = String::new();
-    io::stdin().lock().read_to_string(&mut input_raw).expect("Failed to read input");
-    let input = input_raw.trim();
+// ========== Tests =================================================>
 
-    let begin = Instant::now();
-    let output = run(day, part, input);
-    let duration = begin.elapsed();
+#[test]
+fn testJson()
+{
+    assert_eq!(jsonString("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+    let outcome = Outcome { day: 1, part: 2, input: Some(PathBuf::from("inputs/01.txt")),
+                            result: Ok(String::from("42")), duration: Some(Duration::from_millis(3)) };
+    assert_eq!(outcome.toJson(),
+               r#"{"day": 1, "part": 2, "input": "inputs/01.txt", "answer": "42", "duration_ms": 3, "error": null}"#);
+    let outcome = Outcome { day: 8, part: 1, input: None,
+                            result: Err(String::from("Day 8 is not implemented.")), duration: None };
+    assert_eq!(outcome.toJson(),
+               r#"{"day": 8, "part": 1, "input": null, "answer": null, "duration_ms": null, "error": "Day 8 is not implemented."}"#);
+}
 
-    println!("{}", output);
-    println!("Run time: {}ms",
-             (duration.as_secs() as f64
-              + duration.subsec_nanos() as f64 * 1e-9) * 1000.0);
+#[test]
+fn testTakeJobs()
+{
+    let mut args: Vec<String> = vec!["advent", "-j", "3", "all"].into_iter().map(String::from).collect();
+    assert_eq!(takeJobs(&mut args), Ok(3));
+    assert_eq!(args, vec![String::from("advent"), String::from("all")]);
+    assert!(takeJobs(&mut args).unwrap() >= 1);
+
+    let mut args: Vec<String> = vec!["advent", "all", "-j"].into_iter().map(String::from).collect();
+    assert!(takeJobs(&mut args).is_err());
+    let mut args: Vec<String> = vec!["advent", "--jobs", "0"].into_iter().map(String::from).collect();
+    assert!(takeJobs(&mut args).is_err());
+}
+
+#[test]
+fn testRunCatching()
+{
+    // A program that fails is an error, not a wrong answer.
+    assert_eq!(runCatching(5, 1, "1101,1,1,0,42"), Err(String::from("Invalid opcode 42 at 4")));
+    assert_eq!(runCatching(9, 1, "1,0,0,100000,99"),
+               Err(String::from("Address 100000 is out of memory at 0")));
+    assert_eq!(runCatching(5, 1, "104,7,99"), Ok(String::from("[7]")));
+}
+
+#[test]
+fn testParseBenchArgs()
+{
+    let parse = |args: &[&str]|
+    {
+        parseBenchArgs(&args.iter().map(|arg| String::from(*arg)).collect())
+    };
+    let options = parse(&["advent", "bench", "9", "2", "--time", "0.5"]).unwrap();
+    assert_eq!(options.budget, Some(Duration::from_millis(500)));
+    assert_eq!(options.runs, None);
+    assert_eq!(parse(&["advent", "bench", "9", "2"]).unwrap().runs, Some(10));
+
+    for time in ["0", "-1", "NaN", "inf", "1e300"]
+    {
+        assert_eq!(parse(&["advent", "bench", "9", "2", "--time", time]).map(|_| ()),
+                   Err(format!("Invalid time: {}", time)));
+    }
+    assert!(parse(&["advent", "bench", "9", "2", "--time", "soon"]).is_err());
 }